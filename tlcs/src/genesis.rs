@@ -1,13 +1,49 @@
+use proto_types::Denom;
 use serde::{Deserialize, Serialize};
 
 use auth::GenesisState as AuthGenesis;
 use bank::GenesisState as BankGenesis;
+use timelock::GenesisState as TimelockGenesis;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct GenesisState {
     pub bank: BankGenesis,
     pub auth: AuthGenesis,
+    pub timelock: TimelockGenesis,
+}
+
+impl GenesisState {
+    /// Checks the parts of the genesis which span modules. The timelock module account must
+    /// hold the bonds of the genesis contributors and the escrowed fees since they are paid out
+    /// of it.
+    pub fn validate(&self) -> Result<(), String> {
+        let module_address = timelock::module_address();
+        let module_coins: Vec<_> = self
+            .bank
+            .balances
+            .iter()
+            .filter(|balance| balance.address == module_address)
+            .flat_map(|balance| balance.coins.clone().into_inner())
+            .collect();
+
+        for (denom, required) in self.timelock.module_holdings()? {
+            let coin_denom = Denom::try_from(denom.clone())
+                .map_err(|e| format!("invalid timelock denom {}: {}", denom, e))?;
+            let covered = module_coins
+                .iter()
+                .any(|coin| coin.denom == coin_denom && coin.amount >= required.into());
+
+            if !covered {
+                return Err(format!(
+                    "timelock module account {} must hold {}{} for the contributor bonds and escrowed fees",
+                    module_address, required, denom
+                ));
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl Default for GenesisState {
@@ -29,6 +65,18 @@ impl Default for GenesisState {
                     sig_verify_cost_secp256k1: 1000,
                 },
             },
+            timelock: timelock::GenesisState {
+                keypairs: vec![],
+                contributions: vec![],
+                loe_data: vec![],
+                params: Some(timelock::Params::default().into()),
+                authority: "".into(),
+                contributors: vec![],
+                fee_escrows: vec![],
+                archived_keypairs: vec![],
+                beacons: vec![],
+                contributor_stats: vec![],
+            },
         }
     }
 }
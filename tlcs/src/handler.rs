@@ -20,7 +20,7 @@ use crate::{
 pub struct Handler {
     bank_handler: bank::Handler<TlcsStoreKey, TlcsParamsStoreKey>,
     auth_handler: auth::Handler<TlcsStoreKey, TlcsParamsStoreKey>,
-    timelock_handler: timelock::Handler<TlcsStoreKey>,
+    timelock_handler: timelock::Handler<TlcsStoreKey, TlcsParamsStoreKey>,
 }
 
 impl Handler {
@@ -35,12 +35,18 @@ impl Handler {
 
         let bank_keeper = bank::Keeper::new(
             TlcsStoreKey::Bank,
-            params_keeper,
+            params_keeper.clone(),
             TlcsParamsStoreKey::Bank,
             auth_keeper.clone(),
         );
 
-        let timelock_keeper = timelock::Keeper::new(TlcsStoreKey::Timelock);
+        let timelock_keeper = timelock::Keeper::new(
+            TlcsStoreKey::Timelock,
+            params_keeper,
+            TlcsStoreKey::Params,
+            TlcsParamsStoreKey::Timelock,
+            bank_keeper.clone(),
+        );
 
         Handler {
             bank_handler: bank::Handler::new(bank_keeper),
@@ -67,8 +73,13 @@ impl gears::baseapp::Handler<Message, TlcsStoreKey, GenesisState> for Handler {
         ctx: &mut InitContext<DB, TlcsStoreKey>,
         genesis: GenesisState,
     ) {
+        if let Err(e) = genesis.validate() {
+            panic!("invalid genesis state: {}", e);
+        }
+
         self.bank_handler.init_genesis(ctx, genesis.bank);
         self.auth_handler.init_genesis(ctx, genesis.auth);
+        self.timelock_handler.init_genesis(ctx, genesis.timelock);
     }
 
     fn handle_query<DB: Database>(
@@ -28,6 +28,7 @@ pub enum TlcsParamsStoreKey {
     Bank,
     Auth,
     BaseApp,
+    Timelock,
 }
 
 /// WARNING: a key name must not be a prefix of another, there is currently
@@ -38,6 +39,7 @@ impl ParamsSubspaceKey for TlcsParamsStoreKey {
             Self::Bank => "bank/",
             Self::Auth => "auth/",
             Self::BaseApp => "baseapp/",
+            Self::Timelock => "timelock/",
         }
     }
 }
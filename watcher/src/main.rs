@@ -15,10 +15,6 @@ use std::env;
 use tendermint_rpc::Url;
 use tokio::time::{sleep, Duration};
 
-use timelock::LOE_GENESIS_TIME;
-use timelock::LOE_PERIOD;
-use timelock::LOE_URL;
-
 error_chain! {
     foreign_links {
         EnvVar(env::VarError);
@@ -27,7 +23,8 @@ error_chain! {
 }
 
 use timelock::{
-    proto::tlcs::v1beta1::{MsgLoeData, RawMsgKeyPair},
+    proto::tlcs::v1beta1::{MsgLoeData, RawBeacon, RawMsgKeyPair},
+    registered_beacon,
     utils::run_tx_command,
     Config,
 };
@@ -44,11 +41,15 @@ pub struct LoeData {
     pub round: u64,
     pub randomness: String,
     pub signature: String,
+    /// Only published by chained beacons
+    #[serde(default)]
+    pub previous_signature: String,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct Pairs {
     pub keypairs: Vec<RawMsgKeyPair>,
+    pub beacons: Vec<RawBeacon>,
 }
 
 const APP_NAME: &str = env!("CARGO_PKG_NAME");
@@ -97,16 +98,33 @@ async fn main() -> Result<()> {
         let keypairs: Pairs = response.json().await?;
         //println!("Got keypair data. Len: {}", keypairs.keypairs.len());
 
-        for keypair in keypairs.keypairs {
+        for keypair in &keypairs.keypairs {
+            // The beacons of the listed keypairs come with them, as registered on chain
+            let beacon = match keypairs
+                .beacons
+                .iter()
+                .find(|beacon| beacon.id == keypair.beacon_id)
+            {
+                Some(beacon) => beacon,
+                None => continue,
+            };
+            let latest_round = match registered_beacon(beacon) {
+                Some(registered) => registered.round_at(unix_now()),
+                None => continue,
+            };
+
             //println!("Getting loe data for {}", keypair.round);
-            if keypair.round < current_loe_round() {
-                let loe_data: LoeData =
-                    reqwest::get(format!("{}public/{}", LOE_URL, keypair.round))
-                        .await?
-                        .json()
-                        .await?;
-
-                send_transaction(config.clone(), loe_data);
+            if keypair.round <= latest_round {
+                let loe_data: LoeData = reqwest::get(format!(
+                    "{}/public/{}",
+                    beacon.url.trim_end_matches('/'),
+                    keypair.round
+                ))
+                .await?
+                .json()
+                .await?;
+
+                send_transaction(config.clone(), beacon.id, loe_data);
                 sleep(Duration::from_millis(6100)).await;
             }
         }
@@ -115,9 +133,10 @@ async fn main() -> Result<()> {
         sleep(Duration::from_millis(10000)).await;
     }
 
-    fn send_transaction(config: Config, data: LoeData) {
+    fn send_transaction(config: Config, beacon_id: u32, data: LoeData) {
         let this_round = data.round;
         let this_signature = data.signature;
+        let this_previous_signature = data.previous_signature;
 
         thread::spawn(move || {
             // This must be run inside a thread since it will block until it receives a response
@@ -128,6 +147,8 @@ async fn main() -> Result<()> {
                     address: addr,
                     round: this_round,
                     signature: this_signature,
+                    previous_signature: this_previous_signature,
+                    beacon_id,
                 })
             }) {
                 Ok(_) => println!("Successfully submitted LOE data for {:?}", this_round),
@@ -137,10 +158,9 @@ async fn main() -> Result<()> {
     }
 }
 
-fn current_loe_round() -> u64 {
-    let now = SystemTime::now()
+fn unix_now() -> i64 {
+    SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
-        .as_secs() as i64;
-    ((now - LOE_GENESIS_TIME as i64) / LOE_PERIOD as i64) as u64
+        .as_secs() as i64
 }
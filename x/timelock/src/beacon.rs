@@ -0,0 +1,200 @@
+use drand_verify::{G1Pubkey, Pubkey};
+use tlcs_rust::chain_functions::loe_signature_is_valid;
+
+use crate::proto::tlcs::v1beta1::RawBeacon;
+
+/// Beacon of the keypairs which don't record one, the drand quicknet
+pub const QUICKNET_BEACON_ID: u32 = 0;
+pub const DRAND_DEFAULT_BEACON_ID: u32 = 1;
+
+/// Signature schemes of the registered beacons, named as in the drand chain info
+pub const SCHEME_PEDERSEN_BLS_CHAINED: &str = "pedersen-bls-chained";
+pub const SCHEME_PEDERSEN_BLS_UNCHAINED: &str = "pedersen-bls-unchained";
+pub const SCHEME_BLS_UNCHAINED_G1_RFC9380: &str = "bls-unchained-g1-rfc9380";
+
+const DRAND_API_URL: &str = "https://api.drand.sh";
+
+// Chain info from https://api.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/info
+const QUICKNET_CHAIN_HASH: &str =
+    "52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971";
+const QUICKNET_PUBLIC_KEY: &str = "83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a";
+const QUICKNET_GENESIS_TIME: i64 = 1692803367;
+const QUICKNET_PERIOD: u32 = 3;
+
+// Chain info from https://api.drand.sh/8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce/info
+const DRAND_DEFAULT_CHAIN_HASH: &str =
+    "8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce";
+const DRAND_DEFAULT_PUBLIC_KEY: &str = "868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31";
+const DRAND_DEFAULT_GENESIS_TIME: i64 = 1595431050;
+const DRAND_DEFAULT_PERIOD: u32 = 30;
+
+/// A randomness beacon whose round signatures release the private keys. Signatures are hex
+/// encoded, as they are published by drand.
+pub trait Beacon {
+    /// Returns whether `signature` is the beacon's signature for the round. Chained beacons
+    /// sign the previous signature too, unchained beacons ignore it.
+    fn verify(&self, round: u64, previous_signature: &str, signature: &str) -> bool;
+
+    /// Latest round published at the unix time, 0 before the beacon's genesis
+    fn round_at(&self, time: i64) -> u64;
+
+    /// Hex encoded public key the keyshares are made for
+    fn public_key(&self) -> String;
+
+    /// Whether keys can be timelocked to the beacon's rounds. The keyshares of tlcs-rust are
+    /// encrypted to the identity of a future round, which only works for unchained beacons
+    /// with G1 signatures like the quicknet.
+    fn supports_timelock(&self) -> bool;
+}
+
+/// The beacons registered when the genesis doesn't list any, and by the store migration. A
+/// network with another quicknet deployment lists its beacons in the genesis.
+pub fn default_beacons() -> Vec<RawBeacon> {
+    vec![
+        RawBeacon {
+            id: QUICKNET_BEACON_ID,
+            chain_hash: QUICKNET_CHAIN_HASH.into(),
+            public_key: QUICKNET_PUBLIC_KEY.into(),
+            period: QUICKNET_PERIOD,
+            genesis_time: QUICKNET_GENESIS_TIME,
+            scheme: SCHEME_BLS_UNCHAINED_G1_RFC9380.into(),
+            retired: false,
+            url: format!("{}/{}", DRAND_API_URL, QUICKNET_CHAIN_HASH),
+        },
+        RawBeacon {
+            id: DRAND_DEFAULT_BEACON_ID,
+            chain_hash: DRAND_DEFAULT_CHAIN_HASH.into(),
+            public_key: DRAND_DEFAULT_PUBLIC_KEY.into(),
+            period: DRAND_DEFAULT_PERIOD,
+            genesis_time: DRAND_DEFAULT_GENESIS_TIME,
+            scheme: SCHEME_PEDERSEN_BLS_CHAINED.into(),
+            retired: false,
+            url: format!("{}/{}", DRAND_API_URL, DRAND_DEFAULT_CHAIN_HASH),
+        },
+    ]
+}
+
+/// Returns the beacon described by the registry entry, or `None` if its scheme is unknown
+pub fn registered_beacon(entry: &RawBeacon) -> Option<Box<dyn Beacon>> {
+    match entry.scheme.as_str() {
+        SCHEME_BLS_UNCHAINED_G1_RFC9380 => Some(Box::new(Quicknet {
+            public_key: entry.public_key.clone(),
+            genesis_time: entry.genesis_time,
+            period: entry.period,
+        })),
+        SCHEME_PEDERSEN_BLS_CHAINED | SCHEME_PEDERSEN_BLS_UNCHAINED => {
+            Some(Box::new(PedersenBls {
+                public_key: entry.public_key.clone(),
+                genesis_time: entry.genesis_time,
+                period: entry.period,
+                chained: entry.scheme == SCHEME_PEDERSEN_BLS_CHAINED,
+            }))
+        }
+        _ => None,
+    }
+}
+
+/// Checks a registry entry before it is stored
+pub fn validate_beacon(entry: &RawBeacon) -> Result<(), String> {
+    if entry.chain_hash.is_empty() || hex::decode(&entry.chain_hash).is_err() {
+        return Err(format!("beacon {} has an invalid chain hash", entry.id));
+    }
+    if entry.public_key.is_empty() || hex::decode(&entry.public_key).is_err() {
+        return Err(format!("beacon {} has an invalid public key", entry.id));
+    }
+    if entry.url.is_empty() {
+        return Err(format!("beacon {} has no url", entry.id));
+    }
+    if entry.period == 0 {
+        return Err(format!(
+            "beacon {} period must be greater than zero",
+            entry.id
+        ));
+    }
+    if entry.genesis_time <= 0 {
+        return Err(format!("beacon {} genesis time must be positive", entry.id));
+    }
+    if registered_beacon(entry).is_none() {
+        return Err(format!(
+            "beacon {} has unknown scheme: {}",
+            entry.id, entry.scheme
+        ));
+    }
+
+    Ok(())
+}
+
+/// Latest round of a beacon which publishes round 1 at `genesis_time` and a round every
+/// `period` seconds after
+fn round_at(genesis_time: i64, period: u32, time: i64) -> u64 {
+    if time < genesis_time || period == 0 {
+        0
+    } else {
+        (time - genesis_time) as u64 / period as u64 + 1
+    }
+}
+
+/// Beacons unchained with G1 signatures, like the drand quicknet
+pub struct Quicknet {
+    public_key: String,
+    genesis_time: i64,
+    period: u32,
+}
+
+impl Beacon for Quicknet {
+    fn verify(&self, round: u64, _previous_signature: &str, signature: &str) -> bool {
+        loe_signature_is_valid(round, signature.to_string(), self.public_key.clone())
+    }
+
+    fn round_at(&self, time: i64) -> u64 {
+        round_at(self.genesis_time, self.period, time)
+    }
+
+    fn public_key(&self) -> String {
+        self.public_key.clone()
+    }
+
+    fn supports_timelock(&self) -> bool {
+        true
+    }
+}
+
+/// Beacons with G1 public keys and G2 signatures, like the drand default chain. Unchained
+/// networks of the scheme sign the round only.
+pub struct PedersenBls {
+    public_key: String,
+    genesis_time: i64,
+    period: u32,
+    chained: bool,
+}
+
+impl Beacon for PedersenBls {
+    fn verify(&self, round: u64, previous_signature: &str, signature: &str) -> bool {
+        let verify = || -> Option<bool> {
+            let public_key = G1Pubkey::from_variable(&hex::decode(&self.public_key).ok()?).ok()?;
+            let previous_signature = if self.chained {
+                hex::decode(previous_signature).ok()?
+            } else {
+                vec![]
+            };
+            public_key
+                .verify(round, &previous_signature, &hex::decode(signature).ok()?)
+                .ok()
+        };
+
+        verify().unwrap_or(false)
+    }
+
+    fn round_at(&self, time: i64) -> u64 {
+        round_at(self.genesis_time, self.period, time)
+    }
+
+    fn public_key(&self) -> String {
+        self.public_key.clone()
+    }
+
+    // Round signatures are G2 points and chained beacons sign the previous signature too
+    fn supports_timelock(&self) -> bool {
+        false
+    }
+}
@@ -1,11 +1,18 @@
 use crate::proto::tlcs::v1beta1::{
-    QueryAllContributionsResponse, QueryAllKeyPairsResponse, QueryAllLoeDataResponse,
-    QueryRoundRequest, QueryRoundSchemeRequest, QueryTimeRequest,
+    PageRequest, QueryAddressRequest, QueryAllContributionsResponse, QueryAllKeyPairsResponse,
+    QueryAllLoeDataResponse, QueryArchivedKeyPairsResponse, QueryBeaconsResponse,
+    QueryContributorStatsResponse, QueryContributorsResponse, QueryKeyPairRequest,
+    QueryKeyPairResponse, QueryLoeDataNeededResponse, QueryPendingWorkResponse,
+    QueryPruneStatsResponse, QueryRoundRangeRequest, QueryRoundRequest, QueryRoundSchemeRequest,
+    QueryTimeRangeRequest, QueryTimeRequest,
 };
-use anyhow::Result;
+use crate::{GenesisState, Params, RawParams};
+use anyhow::{anyhow, Result};
 use clap::{Args, Subcommand};
 use gears::client::query::run_query;
 use ibc_proto::protobuf::Protobuf;
+use prost::Message;
+use proto_types::AccAddress;
 
 use tendermint_informal::block::Height;
 
@@ -15,28 +22,150 @@ pub struct QueryCli {
     command: TimelockCommands,
 }
 
+/// Pagination flags of the list queries
+#[derive(Args, Debug, Clone)]
+pub struct PageArgs {
+    /// Maximum number of results to return, the default page size is used if not set
+    #[arg(long, default_value_t = 0)]
+    limit: u64,
+    /// Number of results to skip
+    #[arg(long, default_value_t = 0)]
+    offset: u64,
+    /// Hex encoded key to start the page at, as returned in `next_key`
+    #[arg(long)]
+    page_key: Option<String>,
+}
+
+impl PageArgs {
+    fn page_request(&self) -> Result<PageRequest> {
+        let key = match &self.page_key {
+            Some(page_key) => hex::decode(page_key)?,
+            None => vec![],
+        };
+
+        Ok(PageRequest {
+            key,
+            offset: self.offset,
+            limit: self.limit,
+        })
+    }
+}
+
 #[derive(Subcommand, Debug)]
 pub enum TimelockCommands {
     /// Query list of all contributions
-    Contributions,
+    Contributions {
+        #[command(flatten)]
+        page: PageArgs,
+    },
     /// Query for contributions by round
-    ContributionsByRound { round: u64 },
+    ContributionsByRound {
+        round: u64,
+        #[command(flatten)]
+        page: PageArgs,
+    },
     /// Query for contributions by round and scheme
-    ContributionsByRoundAndScheme { round: u64, scheme: u32 },
+    ContributionsByRoundAndScheme {
+        round: u64,
+        scheme: u32,
+        #[command(flatten)]
+        page: PageArgs,
+    },
+    /// Query for contributions to a single keypair
+    ContributionsByKeypair {
+        round: u64,
+        scheme: u32,
+        id: u32,
+        #[command(flatten)]
+        page: PageArgs,
+    },
     /// Query list of all keypairs
-    Keypairs,
+    Keypairs {
+        #[command(flatten)]
+        page: PageArgs,
+    },
     /// Query for keypairs by round
-    KeypairsByRound { round: u64 },
+    KeypairsByRound {
+        round: u64,
+        #[command(flatten)]
+        page: PageArgs,
+    },
     /// Query for keypairs by time
-    KeypairsByTime { time: i64 },
+    KeypairsByTime {
+        time: i64,
+        #[command(flatten)]
+        page: PageArgs,
+    },
+    /// Query for keypairs of the rounds from `from` to `to`
+    KeypairsByRoundRange {
+        from: u64,
+        to: u64,
+        #[command(flatten)]
+        page: PageArgs,
+    },
+    /// Query for keypairs of the LOE rounds published between two unix times
+    KeypairsByTimeRange {
+        from_unix: i64,
+        to_unix: i64,
+        #[command(flatten)]
+        page: PageArgs,
+    },
+    /// Query for keypairs requested by an address
+    KeypairsByOwner {
+        address: AccAddress,
+        #[command(flatten)]
+        page: PageArgs,
+    },
+    /// Query a single keypair
+    Keypair { round: u64, scheme: u32, id: u32 },
     /// Query for keypairs by round and scheme
-    KeypairsByRoundAndScheme { round: u64, scheme: u32 },
+    KeypairsByRoundAndScheme {
+        round: u64,
+        scheme: u32,
+        #[command(flatten)]
+        page: PageArgs,
+    },
     /// Query list of all LOE data
-    LoeData,
+    LoeData {
+        #[command(flatten)]
+        page: PageArgs,
+    },
     /// Query for LOE data by round
-    LoeDataByRound { round: u64 },
-    /// Query list of keypairs that need loe data
-    LoeDataNeeded,
+    LoeDataByRound {
+        round: u64,
+        #[command(flatten)]
+        page: PageArgs,
+    },
+    /// Query list of keypairs that need loe data, with the beacons to get it from
+    LoeDataNeeded {
+        #[command(flatten)]
+        page: PageArgs,
+    },
+    /// Query list of registered beacons
+    Beacons {
+        #[command(flatten)]
+        page: PageArgs,
+    },
+    /// Query the timelock module params
+    Params,
+    /// Query list of registered contributors
+    Contributors {
+        #[command(flatten)]
+        page: PageArgs,
+    },
+    /// Query the participation of a contributor
+    ContributorStats { address: AccAddress },
+    /// Query the totals of the pruned keypair data
+    PruneStats,
+    /// Query list of archived keypairs
+    ArchivedKeyPairs {
+        #[command(flatten)]
+        page: PageArgs,
+    },
+    /// Query the key derivations carried over to the next block
+    PendingWork,
+    /// Export the timelock module state in genesis format
+    ExportGenesis,
 }
 
 pub fn run_timelock_query_command(
@@ -45,9 +174,9 @@ pub fn run_timelock_query_command(
     height: Option<Height>,
 ) -> Result<String> {
     match args.command {
-        TimelockCommands::Contributions => {
+        TimelockCommands::Contributions { page } => {
             let res = run_query::<QueryAllContributionsResponse, QueryAllContributionsResponse>(
-                vec![],
+                page.page_request()?.encode_to_vec(),
                 "/tlcs.timelock.v1beta1.Query/AllContributions".into(),
                 node,
                 height,
@@ -55,8 +184,11 @@ pub fn run_timelock_query_command(
 
             Ok(serde_json::to_string_pretty(&res)?)
         }
-        TimelockCommands::ContributionsByRound { round } => {
-            let query = QueryRoundRequest { round };
+        TimelockCommands::ContributionsByRound { round, page } => {
+            let query = QueryRoundRequest {
+                round,
+                pagination: Some(page.page_request()?),
+            };
 
             let res = run_query::<QueryAllContributionsResponse, QueryAllContributionsResponse>(
                 query.encode_vec(),
@@ -67,8 +199,16 @@ pub fn run_timelock_query_command(
 
             Ok(serde_json::to_string_pretty(&res)?)
         }
-        TimelockCommands::ContributionsByRoundAndScheme { round, scheme } => {
-            let query = QueryRoundSchemeRequest { round, scheme };
+        TimelockCommands::ContributionsByRoundAndScheme {
+            round,
+            scheme,
+            page,
+        } => {
+            let query = QueryRoundSchemeRequest {
+                round,
+                scheme,
+                pagination: Some(page.page_request()?),
+            };
 
             let res = run_query::<QueryAllContributionsResponse, QueryAllContributionsResponse>(
                 query.encode_vec(),
@@ -79,9 +219,31 @@ pub fn run_timelock_query_command(
 
             Ok(serde_json::to_string_pretty(&res)?)
         }
-        TimelockCommands::Keypairs => {
+        TimelockCommands::ContributionsByKeypair {
+            round,
+            scheme,
+            id,
+            page,
+        } => {
+            let query = QueryKeyPairRequest {
+                round,
+                scheme,
+                id,
+                pagination: Some(page.page_request()?),
+            };
+
+            let res = run_query::<QueryAllContributionsResponse, QueryAllContributionsResponse>(
+                query.encode_vec(),
+                "/tlcs.timelock.v1beta1.Query/AllContributionsByKeyPair".into(),
+                node,
+                height,
+            )?;
+
+            Ok(serde_json::to_string_pretty(&res)?)
+        }
+        TimelockCommands::Keypairs { page } => {
             let res = run_query::<QueryAllKeyPairsResponse, QueryAllKeyPairsResponse>(
-                vec![],
+                page.page_request()?.encode_to_vec(),
                 "/tlcs.timelock.v1beta1.Query/AllKeyPairs".into(),
                 node,
                 height,
@@ -89,8 +251,11 @@ pub fn run_timelock_query_command(
 
             Ok(serde_json::to_string_pretty(&res)?)
         }
-        TimelockCommands::KeypairsByRound { round } => {
-            let query = QueryRoundRequest { round };
+        TimelockCommands::KeypairsByRound { round, page } => {
+            let query = QueryRoundRequest {
+                round,
+                pagination: Some(page.page_request()?),
+            };
 
             let res = run_query::<QueryAllKeyPairsResponse, QueryAllKeyPairsResponse>(
                 query.encode_vec(),
@@ -101,8 +266,11 @@ pub fn run_timelock_query_command(
 
             Ok(serde_json::to_string_pretty(&res)?)
         }
-        TimelockCommands::KeypairsByTime { time } => {
-            let query = QueryTimeRequest { time };
+        TimelockCommands::KeypairsByTime { time, page } => {
+            let query = QueryTimeRequest {
+                time,
+                pagination: Some(page.page_request()?),
+            };
 
             let res = run_query::<QueryAllKeyPairsResponse, QueryAllKeyPairsResponse>(
                 query.encode_vec(),
@@ -113,8 +281,87 @@ pub fn run_timelock_query_command(
 
             Ok(serde_json::to_string_pretty(&res)?)
         }
-        TimelockCommands::KeypairsByRoundAndScheme { round, scheme } => {
-            let query = QueryRoundSchemeRequest { round, scheme };
+        TimelockCommands::KeypairsByRoundRange { from, to, page } => {
+            let query = QueryRoundRangeRequest {
+                from,
+                to,
+                pagination: Some(page.page_request()?),
+            };
+
+            let res = run_query::<QueryAllKeyPairsResponse, QueryAllKeyPairsResponse>(
+                query.encode_vec(),
+                "/tlcs.timelock.v1beta1.Query/KeypairsByRoundRange".into(),
+                node,
+                height,
+            )?;
+
+            Ok(serde_json::to_string_pretty(&res)?)
+        }
+        TimelockCommands::KeypairsByTimeRange {
+            from_unix,
+            to_unix,
+            page,
+        } => {
+            let query = QueryTimeRangeRequest {
+                from_unix,
+                to_unix,
+                pagination: Some(page.page_request()?),
+            };
+
+            let res = run_query::<QueryAllKeyPairsResponse, QueryAllKeyPairsResponse>(
+                query.encode_vec(),
+                "/tlcs.timelock.v1beta1.Query/KeypairsByTimeRange".into(),
+                node,
+                height,
+            )?;
+
+            Ok(serde_json::to_string_pretty(&res)?)
+        }
+        TimelockCommands::KeypairsByOwner { address, page } => {
+            let query = QueryAddressRequest {
+                address,
+                pagination: Some(page.page_request()?),
+            };
+
+            let res = run_query::<QueryAllKeyPairsResponse, QueryAllKeyPairsResponse>(
+                query.encode_vec(),
+                "/tlcs.timelock.v1beta1.Query/KeypairsByOwner".into(),
+                node,
+                height,
+            )?;
+
+            Ok(serde_json::to_string_pretty(&res)?)
+        }
+        TimelockCommands::Keypair { round, scheme, id } => {
+            let query = QueryKeyPairRequest {
+                round,
+                scheme,
+                id,
+                pagination: None,
+            };
+
+            let res = run_query::<QueryKeyPairResponse, QueryKeyPairResponse>(
+                query.encode_vec(),
+                "/tlcs.timelock.v1beta1.Query/KeyPair".into(),
+                node,
+                height,
+            )?;
+            if res.keypair.is_none() {
+                return Err(anyhow!("keypair not found"));
+            }
+
+            Ok(serde_json::to_string_pretty(&res)?)
+        }
+        TimelockCommands::KeypairsByRoundAndScheme {
+            round,
+            scheme,
+            page,
+        } => {
+            let query = QueryRoundSchemeRequest {
+                round,
+                scheme,
+                pagination: Some(page.page_request()?),
+            };
 
             let res = run_query::<QueryAllKeyPairsResponse, QueryAllKeyPairsResponse>(
                 query.encode_vec(),
@@ -125,9 +372,9 @@ pub fn run_timelock_query_command(
 
             Ok(serde_json::to_string_pretty(&res)?)
         }
-        TimelockCommands::LoeData => {
+        TimelockCommands::LoeData { page } => {
             let res = run_query::<QueryAllLoeDataResponse, QueryAllLoeDataResponse>(
-                vec![],
+                page.page_request()?.encode_to_vec(),
                 "/tlcs.timelock.v1beta1.Query/AllLoeData".into(),
                 node,
                 height,
@@ -135,8 +382,11 @@ pub fn run_timelock_query_command(
 
             Ok(serde_json::to_string_pretty(&res)?)
         }
-        TimelockCommands::LoeDataByRound { round } => {
-            let query = QueryRoundRequest { round };
+        TimelockCommands::LoeDataByRound { round, page } => {
+            let query = QueryRoundRequest {
+                round,
+                pagination: Some(page.page_request()?),
+            };
 
             let res = run_query::<QueryAllLoeDataResponse, QueryAllLoeDataResponse>(
                 query.encode_vec(),
@@ -147,14 +397,99 @@ pub fn run_timelock_query_command(
 
             Ok(serde_json::to_string_pretty(&res)?)
         }
-        TimelockCommands::LoeDataNeeded => {
-            let res = run_query::<QueryAllKeyPairsResponse, QueryAllKeyPairsResponse>(
-                vec![],
+        TimelockCommands::LoeDataNeeded { page } => {
+            let res = run_query::<QueryLoeDataNeededResponse, QueryLoeDataNeededResponse>(
+                page.page_request()?.encode_to_vec(),
                 "/tlcs.timelock.v1beta1.Query/AllLoeDataNeeded".into(),
                 node,
                 height,
             )?;
 
+            Ok(serde_json::to_string_pretty(&res)?)
+        }
+        TimelockCommands::Beacons { page } => {
+            let res = run_query::<QueryBeaconsResponse, QueryBeaconsResponse>(
+                page.page_request()?.encode_to_vec(),
+                "/tlcs.timelock.v1beta1.Query/Beacons".into(),
+                node,
+                height,
+            )?;
+
+            Ok(serde_json::to_string_pretty(&res)?)
+        }
+        TimelockCommands::Params => {
+            let res = run_query::<Params, RawParams>(
+                vec![],
+                "/tlcs.timelock.v1beta1.Query/Params".into(),
+                node,
+                height,
+            )?;
+
+            Ok(serde_json::to_string_pretty(&res)?)
+        }
+        TimelockCommands::Contributors { page } => {
+            let res = run_query::<QueryContributorsResponse, QueryContributorsResponse>(
+                page.page_request()?.encode_to_vec(),
+                "/tlcs.timelock.v1beta1.Query/Contributors".into(),
+                node,
+                height,
+            )?;
+
+            Ok(serde_json::to_string_pretty(&res)?)
+        }
+        TimelockCommands::ContributorStats { address } => {
+            let query = QueryAddressRequest {
+                address,
+                pagination: None,
+            };
+
+            let res = run_query::<QueryContributorStatsResponse, QueryContributorStatsResponse>(
+                query.encode_vec(),
+                "/tlcs.timelock.v1beta1.Query/ContributorStats".into(),
+                node,
+                height,
+            )?;
+
+            Ok(serde_json::to_string_pretty(&res)?)
+        }
+        TimelockCommands::PruneStats => {
+            let res = run_query::<QueryPruneStatsResponse, QueryPruneStatsResponse>(
+                vec![],
+                "/tlcs.timelock.v1beta1.Query/PruneStats".into(),
+                node,
+                height,
+            )?;
+
+            Ok(serde_json::to_string_pretty(&res)?)
+        }
+        TimelockCommands::ArchivedKeyPairs { page } => {
+            let res = run_query::<QueryArchivedKeyPairsResponse, QueryArchivedKeyPairsResponse>(
+                page.page_request()?.encode_to_vec(),
+                "/tlcs.timelock.v1beta1.Query/AllArchivedKeyPairs".into(),
+                node,
+                height,
+            )?;
+
+            Ok(serde_json::to_string_pretty(&res)?)
+        }
+        TimelockCommands::PendingWork => {
+            let res = run_query::<QueryPendingWorkResponse, QueryPendingWorkResponse>(
+                vec![],
+                "/tlcs.timelock.v1beta1.Query/PendingWork".into(),
+                node,
+                height,
+            )?;
+
+            Ok(serde_json::to_string_pretty(&res)?)
+        }
+        TimelockCommands::ExportGenesis => {
+            let res = run_query::<GenesisState, GenesisState>(
+                vec![],
+                "/tlcs.timelock.v1beta1.Query/ExportGenesis".into(),
+                node,
+                height,
+            )?;
+
             Ok(serde_json::to_string_pretty(&res)?)
         }
     }
@@ -1,8 +1,15 @@
 use crate::keeper::scheme_to_string;
-use crate::proto::tlcs::v1beta1::{MsgContribution, MsgLoeData, MsgMultiNewProcess, MsgNewProcess};
-use anyhow::Result;
+use crate::proto::tlcs::v1beta1::{
+    MsgCancelKeyRequest, MsgContribution, MsgDeregisterContributor, MsgLoeData, MsgMultiNewProcess,
+    MsgNewProcess, MsgRegisterContributor, MsgUpdateBeacon, MsgUpdateParams, PageRequest,
+    QueryBeaconsResponse, QueryKeyPairRequest, QueryKeyPairResponse, RawBeacon, RawMsgKeyPair,
+};
+use anyhow::{anyhow, Result};
 use clap::{Args, Subcommand};
 use drand_core::HttpClient as DrandHttpClient;
+use gears::client::query::run_query;
+use ibc_proto::protobuf::Protobuf;
+use prost::Message;
 use proto_types::AccAddress;
 use std::process::exit;
 use std::str;
@@ -11,9 +18,10 @@ use tokio::runtime::Runtime;
 use crate::Message as TimelockMessage;
 use tlcs_rust::chain_functions::make_keyshare;
 
-use crate::LOE_PUBLIC_KEY;
-use crate::LOE_URL;
-use crate::SECURITY_PARAM;
+use crate::{registered_beacon, Params, RawParams};
+use crate::{QUICKNET_BEACON_ID, SCHEME_PEDERSEN_BLS_CHAINED};
+
+const DEFAULT_QUERY_NODE: &str = "http://localhost:26657";
 
 #[derive(Args, Debug)]
 pub struct Cli {
@@ -33,6 +41,12 @@ pub enum TimelockCommands {
         scheme: u32,
         /// Time that public key should be generated. Time is in unix timestamp format.
         public_key_time: i64,
+        /// Share the canonical keypair for the round and scheme if one was already requested.
+        #[arg(long)]
+        canonical: bool,
+        /// Registered beacon whose round releases the private key.
+        #[arg(long, default_value_t = QUICKNET_BEACON_ID)]
+        beacon_id: u32,
     },
     /// Request new keypair for multiple round and schemes
     MultiKeypair {
@@ -49,6 +63,9 @@ pub enum TimelockCommands {
         schemes: Vec<u32>,
         /// Time that public key should be generated. Time is in unix timestamp format.
         public_key_time: i64,
+        /// Registered beacon whose rounds release the private keys.
+        #[arg(long, default_value_t = QUICKNET_BEACON_ID)]
+        beacon_id: u32,
     },
     /// Send contribution data for given round and scheme
     Contribute {
@@ -58,14 +75,101 @@ pub enum TimelockCommands {
         scheme: u32,
         /// Contribution ID.
         id: u32,
+        /// Node the keypair, its beacon and the security parameter are queried from.
+        #[arg(long, default_value = DEFAULT_QUERY_NODE)]
+        query_node: String,
     },
     /// Submit LOE data
     Submit {
         /// LOE round number.
         round: u64,
+        /// Registered beacon whose signature of the round is submitted.
+        #[arg(long, default_value_t = QUICKNET_BEACON_ID)]
+        beacon_id: u32,
+        /// Node the beacon is queried from.
+        #[arg(long, default_value = DEFAULT_QUERY_NODE)]
+        query_node: String,
         // Signature.
         //signature: Option<String>,
     },
+    /// Schedule a params update. Must be sent by the timelock authority.
+    UpdateParams {
+        /// Block height at which the new params take effect.
+        height: u64,
+        /// Security parameter used for keyshare generation.
+        #[arg(long)]
+        security_param: Option<u32>,
+        /// Minimum number of contributions needed to make a public key.
+        #[arg(long)]
+        min_contribution_threshold: Option<u32>,
+        /// Maximum number of keypairs in a multi keypair request.
+        #[arg(long)]
+        max_multi_requests: Option<u32>,
+        /// Minimum number of seconds between the block time and the public key time.
+        #[arg(long)]
+        min_pubkey_lead_time: Option<u32>,
+        /// Denom of the contributor bond.
+        #[arg(long)]
+        bond_denom: Option<String>,
+        /// Amount which contributors must bond to register.
+        #[arg(long)]
+        contributor_bond: Option<u64>,
+        /// Numerator of the fraction of registered contributors needed to make a public key.
+        #[arg(long)]
+        threshold_numerator: Option<u32>,
+        /// Denominator of the fraction of registered contributors needed to make a public key.
+        #[arg(long)]
+        threshold_denominator: Option<u32>,
+        /// Fee for every requested keypair, in the bond denom.
+        #[arg(long)]
+        request_fee: Option<u64>,
+        /// Minimum participation in parts per thousand before a contributor is jailed. 0 disables jailing.
+        #[arg(long)]
+        min_participation_ratio: Option<u32>,
+        /// Blocks a released keypair is kept before it is archived. 0 keeps them forever.
+        #[arg(long)]
+        retention_blocks: Option<u64>,
+        /// Maximum number of keypairs archived in a single block.
+        #[arg(long)]
+        max_prunes_per_block: Option<u32>,
+        /// Maximum number of public and private keys made in a single block.
+        #[arg(long)]
+        max_key_derivations_per_block: Option<u32>,
+    },
+    /// Register as a contributor. The contributor bond is held until deregistration.
+    RegisterContributor,
+    /// Deregister as a contributor and get the bond back
+    DeregisterContributor,
+    /// Cancel a keypair request before its public key is made. The request fee is refunded.
+    CancelKeyRequest {
+        /// LOE round number.
+        round: u64,
+        /// Key generation scheme.
+        scheme: u32,
+        /// Id of the keypair within the round and scheme.
+        id: u32,
+    },
+    /// Register a beacon, or retire or reactivate a registered one. Must be sent by the
+    /// timelock authority.
+    UpdateBeacon {
+        /// Beacon id.
+        id: u32,
+        /// Hex encoded drand chain hash.
+        chain_hash: String,
+        /// Hex encoded public key of the beacon.
+        public_key: String,
+        /// Seconds between rounds.
+        period: u32,
+        /// Time of the first round. Time is in unix timestamp format.
+        genesis_time: i64,
+        /// Signature scheme as named in the drand chain info, e.g. pedersen-bls-chained.
+        scheme: String,
+        /// Url of the beacon's chain on a drand http api, e.g. https://api.drand.sh/<chain hash>.
+        url: String,
+        /// New keypairs can't be requested for a retired beacon.
+        #[arg(long)]
+        retired: bool,
+    },
 }
 
 pub fn run_timelock_tx_command(args: Cli, from_address: AccAddress) -> Result<TimelockMessage> {
@@ -74,11 +178,15 @@ pub fn run_timelock_tx_command(args: Cli, from_address: AccAddress) -> Result<Ti
             round,
             scheme,
             public_key_time,
+            canonical,
+            beacon_id,
         } => Ok(TimelockMessage::NewProcess(MsgNewProcess {
             address: from_address,
             round,
             scheme,
             pubkey_time: public_key_time,
+            canonical,
+            beacon_id,
         })),
         TimelockCommands::MultiKeypair {
             startround,
@@ -86,6 +194,7 @@ pub fn run_timelock_tx_command(args: Cli, from_address: AccAddress) -> Result<Ti
             roundstep,
             schemes,
             public_key_time,
+            beacon_id,
         } => Ok(TimelockMessage::MultiNewProcess(MsgMultiNewProcess {
             address: from_address,
             startround,
@@ -93,14 +202,37 @@ pub fn run_timelock_tx_command(args: Cli, from_address: AccAddress) -> Result<Ti
             roundstep,
             schemes,
             pubkey_time: public_key_time,
+            beacon_id,
         })),
-        TimelockCommands::Contribute { round, scheme, id } => {
+        TimelockCommands::Contribute {
+            round,
+            scheme,
+            id,
+            query_node,
+        } => {
+            // The keyshare is made for the beacon and security parameter currently on chain
+            let keypair = query_keypair(&query_node, round, scheme, id)?;
+            let beacon = query_beacon(&query_node, keypair.beacon_id)?;
+            if !registered_beacon(&beacon).map_or(false, |b| b.supports_timelock()) {
+                return Err(anyhow!(
+                    "keys can't be timelocked to beacon {} with scheme {}",
+                    beacon.id,
+                    beacon.scheme
+                ));
+            }
+            let params = run_query::<Params, RawParams>(
+                vec![],
+                "/tlcs.timelock.v1beta1.Query/Params".into(),
+                &query_node,
+                None,
+            )?;
+
             //let round_data_vec = generate_participant_data(round);
             let round_data_vec = make_keyshare(
-                LOE_PUBLIC_KEY.into(),
+                beacon.public_key,
                 round,
                 scheme_to_string(scheme),
-                SECURITY_PARAM,
+                params.security_param as usize,
             );
 
             Ok(TimelockMessage::Participate(MsgContribution {
@@ -111,13 +243,18 @@ pub fn run_timelock_tx_command(args: Cli, from_address: AccAddress) -> Result<Ti
                 data: round_data_vec,
             }))
         }
-        TimelockCommands::Submit { round } => {
+        TimelockCommands::Submit {
+            round,
+            beacon_id,
+            query_node,
+        } => {
             // TODO make this so that signature can be passed in and not automatically retrieved
+            let beacon = query_beacon(&query_node, beacon_id)?;
 
             println!("Retrieving LOE Data from API");
-            let loe_data = match Runtime::new()
+            let (loe_data, previous_signature) = match Runtime::new()
                 .expect("unclear why this would ever fail")
-                .block_on(get_loe_data(round))
+                .block_on(get_loe_data(&beacon, round))
             {
                 Ok(dat) => dat,
                 Err(e) => {
@@ -133,18 +270,140 @@ pub fn run_timelock_tx_command(args: Cli, from_address: AccAddress) -> Result<Ti
                 address: from_address,
                 round,
                 signature: loe_data,
+                previous_signature,
+                beacon_id,
+            }))
+        }
+        TimelockCommands::UpdateParams {
+            height,
+            security_param,
+            min_contribution_threshold,
+            max_multi_requests,
+            min_pubkey_lead_time,
+            bond_denom,
+            contributor_bond,
+            threshold_numerator,
+            threshold_denominator,
+            request_fee,
+            min_participation_ratio,
+            retention_blocks,
+            max_prunes_per_block,
+            max_key_derivations_per_block,
+        } => Ok(TimelockMessage::UpdateParams(MsgUpdateParams {
+            authority: from_address,
+            height,
+            security_param,
+            min_contribution_threshold,
+            max_multi_requests,
+            min_pubkey_lead_time,
+            bond_denom,
+            contributor_bond,
+            threshold_numerator,
+            threshold_denominator,
+            request_fee,
+            min_participation_ratio,
+            retention_blocks,
+            max_prunes_per_block,
+            max_key_derivations_per_block,
+        })),
+        TimelockCommands::RegisterContributor => Ok(TimelockMessage::RegisterContributor(
+            MsgRegisterContributor {
+                address: from_address,
+            },
+        )),
+        TimelockCommands::DeregisterContributor => Ok(TimelockMessage::DeregisterContributor(
+            MsgDeregisterContributor {
+                address: from_address,
+            },
+        )),
+        TimelockCommands::CancelKeyRequest { round, scheme, id } => {
+            Ok(TimelockMessage::CancelKeyRequest(MsgCancelKeyRequest {
+                address: from_address,
+                round,
+                scheme,
+                id,
             }))
         }
+        TimelockCommands::UpdateBeacon {
+            id,
+            chain_hash,
+            public_key,
+            period,
+            genesis_time,
+            scheme,
+            url,
+            retired,
+        } => Ok(TimelockMessage::UpdateBeacon(MsgUpdateBeacon {
+            authority: from_address,
+            beacon: RawBeacon {
+                id,
+                chain_hash,
+                public_key,
+                period,
+                genesis_time,
+                scheme,
+                retired,
+                url,
+            },
+        })),
     }
 }
 
-pub async fn get_loe_data(round: u64) -> Result<String> {
-    // Create a new client and retrieve the latest beacon. By default, it verifies its signature against the chain info.
-    let client: DrandHttpClient = LOE_URL.try_into().unwrap();
-    match client.get(round).await {
-        Ok(dat) => Ok(hex::encode(dat.signature())),
-        Err(e) => Err(e),
+fn query_keypair(node: &str, round: u64, scheme: u32, id: u32) -> Result<RawMsgKeyPair> {
+    let query = QueryKeyPairRequest {
+        round,
+        scheme,
+        id,
+        pagination: None,
+    };
+
+    run_query::<QueryKeyPairResponse, QueryKeyPairResponse>(
+        query.encode_vec(),
+        "/tlcs.timelock.v1beta1.Query/KeyPair".into(),
+        node,
+        None,
+    )?
+    .keypair
+    .ok_or_else(|| anyhow!("keypair not found"))
+}
+
+fn query_beacon(node: &str, id: u32) -> Result<RawBeacon> {
+    let mut page = PageRequest::default();
+    loop {
+        let res = run_query::<QueryBeaconsResponse, QueryBeaconsResponse>(
+            page.encode_to_vec(),
+            "/tlcs.timelock.v1beta1.Query/Beacons".into(),
+            node,
+            None,
+        )?;
+
+        if let Some(beacon) = res.beacons.into_iter().find(|beacon| beacon.id == id) {
+            return Ok(beacon);
+        }
+        match res.pagination {
+            Some(next) if !next.next_key.is_empty() => page.key = next.next_key,
+            _ => return Err(anyhow!("beacon {} is not registered", id)),
+        }
     }
+}
+
+/// Returns the beacon's signature of the round and, for chained beacons, the signature of the
+/// round before it which was signed with it
+pub async fn get_loe_data(beacon: &RawBeacon, round: u64) -> Result<(String, String)> {
+    // Create a new client and retrieve the round. By default, it verifies its signature against the chain info.
+    let url = beacon.url.trim_end_matches('/');
+    let client: DrandHttpClient = url
+        .try_into()
+        .map_err(|_| anyhow!("invalid beacon url: {}", beacon.url))?;
+
+    let signature = hex::encode(client.get(round).await?.signature());
+    let previous_signature = if beacon.scheme == SCHEME_PEDERSEN_BLS_CHAINED && round > 1 {
+        hex::encode(client.get(round - 1).await?.signature())
+    } else {
+        "".into()
+    };
+
+    Ok((signature, previous_signature))
 
     // If you just want the latest use this instead
     //let latest = client.latest().await.unwrap();
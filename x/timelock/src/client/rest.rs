@@ -5,6 +5,7 @@ use tendermint_abci::Application;
 use axum::{
     body::Body,
     extract::{Path, Query, State},
+    http::StatusCode,
     routing::get,
     Json, Router,
 };
@@ -13,17 +14,51 @@ use gears::{
         ante::{AuthKeeper, BankKeeper},
         BaseApp, Genesis, Handler,
     },
-    client::rest::{error::Error, Pagination, RestState},
+    client::rest::{error::Error, RestState},
     x::params::ParamsSubspaceKey,
 };
+use prost::Message as ProstMessage;
 use proto_messages::cosmos::tx::v1beta1::Message;
+use proto_types::AccAddress;
+use serde::Deserialize;
 use store::StoreKey;
 use tendermint_proto::abci::RequestQuery;
 
 use crate::proto::tlcs::v1beta1::{
-    QueryAllContributionsResponse, QueryAllKeyPairsResponse, QueryAllLoeDataResponse,
-    QueryRoundRequest, QueryRoundSchemeRequest, QueryTimeRequest,
+    PageRequest, QueryAddressRequest, QueryAllContributionsResponse, QueryAllKeyPairsResponse,
+    QueryAllLoeDataResponse, QueryBeaconsResponse, QueryContributorStatsResponse,
+    QueryContributorsResponse, QueryKeyPairRequest, QueryKeyPairResponse,
+    QueryLoeDataNeededResponse, QueryRoundRangeRequest, QueryRoundRequest, QueryRoundSchemeRequest,
+    QueryTimeRangeRequest, QueryTimeRequest,
 };
+use crate::{Params, TimelockError};
+
+/// Pagination query string of the list endpoints. `page_key` is the hex encoded `next_key`
+/// of the previous page.
+#[derive(Deserialize, Debug, Default)]
+pub struct PageQuery {
+    pub offset: Option<u64>,
+    pub limit: Option<u64>,
+    pub page_key: Option<String>,
+}
+
+impl TryFrom<PageQuery> for PageRequest {
+    type Error = Error;
+
+    fn try_from(query: PageQuery) -> Result<Self, Self::Error> {
+        let key = match query.page_key {
+            Some(page_key) => hex::decode(page_key)
+                .map_err(|e| Error::bad_request(format!("invalid page key: {}", e)))?,
+            None => vec![],
+        };
+
+        Ok(PageRequest {
+            key,
+            offset: query.offset.unwrap_or_default(),
+            limit: query.limit.unwrap_or_default(),
+        })
+    }
+}
 
 /// Get all contributions
 pub async fn get_all_contributions<
@@ -35,11 +70,11 @@ pub async fn get_all_contributions<
     H: Handler<M, SK, G>,
     G: Genesis,
 >(
-    _pagination: Query<Pagination>,
+    Query(page): Query<PageQuery>,
     State(app): State<BaseApp<SK, PSK, M, BK, AK, H, G>>,
 ) -> Result<Json<QueryAllContributionsResponse>, Error> {
     let request = RequestQuery {
-        data: Default::default(),
+        data: PageRequest::try_from(page)?.encode_to_vec().into(),
         path: "/tlcs.timelock.v1beta1.Query/AllContributions".into(),
         height: 0,
         prove: false,
@@ -64,10 +99,13 @@ pub async fn get_contributions_by_round<
     G: Genesis,
 >(
     Path(round): Path<u64>,
-    _pagination: Query<Pagination>,
+    Query(page): Query<PageQuery>,
     State(app): State<BaseApp<SK, PSK, M, BK, AK, H, G>>,
 ) -> Result<Json<QueryAllContributionsResponse>, Error> {
-    let req = QueryRoundRequest { round };
+    let req = QueryRoundRequest {
+        round,
+        pagination: Some(page.try_into()?),
+    };
     let request = RequestQuery {
         data: req.encode_vec().into(),
         path: "/tlcs.timelock.v1beta1.Query/AllContributionsByRound".into(),
@@ -94,10 +132,14 @@ pub async fn get_contributions_by_round_and_scheme<
     G: Genesis,
 >(
     Path((round, scheme)): Path<(u64, u32)>,
-    _pagination: Query<Pagination>,
+    Query(page): Query<PageQuery>,
     State(app): State<BaseApp<SK, PSK, M, BK, AK, H, G>>,
 ) -> Result<Json<QueryAllContributionsResponse>, Error> {
-    let req = QueryRoundSchemeRequest { round, scheme };
+    let req = QueryRoundSchemeRequest {
+        round,
+        scheme,
+        pagination: Some(page.try_into()?),
+    };
     let request = RequestQuery {
         data: req.encode_vec().into(),
         path: "/tlcs.timelock.v1beta1.Query/AllContributionsByRoundAndScheme".into(),
@@ -113,6 +155,41 @@ pub async fn get_contributions_by_round_and_scheme<
     ))
 }
 
+/// Get contributions to a single keypair
+pub async fn get_contributions_by_keypair<
+    SK: StoreKey,
+    PSK: ParamsSubspaceKey,
+    M: Message,
+    BK: BankKeeper<SK>,
+    AK: AuthKeeper<SK>,
+    H: Handler<M, SK, G>,
+    G: Genesis,
+>(
+    Path((round, scheme, id)): Path<(u64, u32, u32)>,
+    Query(page): Query<PageQuery>,
+    State(app): State<BaseApp<SK, PSK, M, BK, AK, H, G>>,
+) -> Result<Json<QueryAllContributionsResponse>, Error> {
+    let req = QueryKeyPairRequest {
+        round,
+        scheme,
+        id,
+        pagination: Some(page.try_into()?),
+    };
+    let request = RequestQuery {
+        data: req.encode_vec().into(),
+        path: "/tlcs.timelock.v1beta1.Query/AllContributionsByKeyPair".into(),
+        height: 0,
+        prove: false,
+    };
+
+    let response = app.query(request);
+
+    Ok(Json(
+        QueryAllContributionsResponse::decode(response.value)
+            .expect("should be a valid QueryAllContributionsResponse"),
+    ))
+}
+
 /// Get all keypairs
 pub async fn get_all_keypairs<
     SK: StoreKey,
@@ -123,11 +200,11 @@ pub async fn get_all_keypairs<
     H: Handler<M, SK, G>,
     G: Genesis,
 >(
-    _pagination: Query<Pagination>,
+    Query(page): Query<PageQuery>,
     State(app): State<BaseApp<SK, PSK, M, BK, AK, H, G>>,
 ) -> Result<Json<QueryAllKeyPairsResponse>, Error> {
     let request = RequestQuery {
-        data: Bytes::new(),
+        data: PageRequest::try_from(page)?.encode_to_vec().into(),
         path: "/tlcs.timelock.v1beta1.Query/AllKeyPairs".into(),
         height: 0,
         prove: false,
@@ -152,10 +229,13 @@ pub async fn get_keypairs_by_round<
     G: Genesis,
 >(
     Path(round): Path<u64>,
-    _pagination: Query<Pagination>,
+    Query(page): Query<PageQuery>,
     State(app): State<BaseApp<SK, PSK, M, BK, AK, H, G>>,
 ) -> Result<Json<QueryAllKeyPairsResponse>, Error> {
-    let req = QueryRoundRequest { round };
+    let req = QueryRoundRequest {
+        round,
+        pagination: Some(page.try_into()?),
+    };
     let request = RequestQuery {
         data: req.encode_vec().into(),
         path: "/tlcs.timelock.v1beta1.Query/AllKeyPairsByRound".into(),
@@ -182,10 +262,13 @@ pub async fn get_keypairs_by_time<
     G: Genesis,
 >(
     Path(time): Path<i64>,
-    _pagination: Query<Pagination>,
+    Query(page): Query<PageQuery>,
     State(app): State<BaseApp<SK, PSK, M, BK, AK, H, G>>,
 ) -> Result<Json<QueryAllKeyPairsResponse>, Error> {
-    let req = QueryTimeRequest { time };
+    let req = QueryTimeRequest {
+        time,
+        pagination: Some(page.try_into()?),
+    };
     let request = RequestQuery {
         data: req.encode_vec().into(),
         path: "/tlcs.timelock.v1beta1.Query/AllKeyPairsByTime".into(),
@@ -201,6 +284,148 @@ pub async fn get_keypairs_by_time<
     ))
 }
 
+/// Get all keys for the rounds from `from` to `to`
+pub async fn get_keypairs_by_round_range<
+    SK: StoreKey,
+    PSK: ParamsSubspaceKey,
+    M: Message,
+    BK: BankKeeper<SK>,
+    AK: AuthKeeper<SK>,
+    H: Handler<M, SK, G>,
+    G: Genesis,
+>(
+    Path((from, to)): Path<(u64, u64)>,
+    Query(page): Query<PageQuery>,
+    State(app): State<BaseApp<SK, PSK, M, BK, AK, H, G>>,
+) -> Result<Json<QueryAllKeyPairsResponse>, Error> {
+    let req = QueryRoundRangeRequest {
+        from,
+        to,
+        pagination: Some(page.try_into()?),
+    };
+    let request = RequestQuery {
+        data: req.encode_vec().into(),
+        path: "/tlcs.timelock.v1beta1.Query/KeypairsByRoundRange".into(),
+        height: 0,
+        prove: false,
+    };
+
+    let response = app.query(request);
+
+    Ok(Json(
+        QueryAllKeyPairsResponse::decode(response.value)
+            .expect("should be a valid QueryAllKeyPairsResponse"),
+    ))
+}
+
+/// Get all keys for the LOE rounds published between two unix times
+pub async fn get_keypairs_by_time_range<
+    SK: StoreKey,
+    PSK: ParamsSubspaceKey,
+    M: Message,
+    BK: BankKeeper<SK>,
+    AK: AuthKeeper<SK>,
+    H: Handler<M, SK, G>,
+    G: Genesis,
+>(
+    Path((from_unix, to_unix)): Path<(i64, i64)>,
+    Query(page): Query<PageQuery>,
+    State(app): State<BaseApp<SK, PSK, M, BK, AK, H, G>>,
+) -> Result<Json<QueryAllKeyPairsResponse>, Error> {
+    let req = QueryTimeRangeRequest {
+        from_unix,
+        to_unix,
+        pagination: Some(page.try_into()?),
+    };
+    let request = RequestQuery {
+        data: req.encode_vec().into(),
+        path: "/tlcs.timelock.v1beta1.Query/KeypairsByTimeRange".into(),
+        height: 0,
+        prove: false,
+    };
+
+    let response = app.query(request);
+
+    Ok(Json(
+        QueryAllKeyPairsResponse::decode(response.value)
+            .expect("should be a valid QueryAllKeyPairsResponse"),
+    ))
+}
+
+/// Get a single keypair. Responds with 404 if the keypair doesn't exist.
+pub async fn get_keypair<
+    SK: StoreKey,
+    PSK: ParamsSubspaceKey,
+    M: Message,
+    BK: BankKeeper<SK>,
+    AK: AuthKeeper<SK>,
+    H: Handler<M, SK, G>,
+    G: Genesis,
+>(
+    Path((round, scheme, id)): Path<(u64, u32, u32)>,
+    State(app): State<BaseApp<SK, PSK, M, BK, AK, H, G>>,
+) -> Result<Json<QueryKeyPairResponse>, (StatusCode, String)> {
+    let req = QueryKeyPairRequest {
+        round,
+        scheme,
+        id,
+        pagination: None,
+    };
+    let request = RequestQuery {
+        data: req.encode_vec().into(),
+        path: "/tlcs.timelock.v1beta1.Query/KeyPair".into(),
+        height: 0,
+        prove: false,
+    };
+
+    let response = app.query(request);
+
+    if response.code == TimelockError::KeyPairNotFound.code() {
+        return Err((StatusCode::NOT_FOUND, response.log));
+    }
+    if response.code != 0 {
+        return Err((StatusCode::INTERNAL_SERVER_ERROR, response.log));
+    }
+
+    Ok(Json(
+        QueryKeyPairResponse::decode(response.value)
+            .expect("should be a valid QueryKeyPairResponse"),
+    ))
+}
+
+/// Get all keys requested by an address
+pub async fn get_keypairs_by_owner<
+    SK: StoreKey,
+    PSK: ParamsSubspaceKey,
+    M: Message,
+    BK: BankKeeper<SK>,
+    AK: AuthKeeper<SK>,
+    H: Handler<M, SK, G>,
+    G: Genesis,
+>(
+    Path(address): Path<AccAddress>,
+    Query(page): Query<PageQuery>,
+    State(app): State<BaseApp<SK, PSK, M, BK, AK, H, G>>,
+) -> Result<Json<QueryAllKeyPairsResponse>, Error> {
+    let req = QueryAddressRequest {
+        address,
+        pagination: Some(page.try_into()?),
+    };
+    let request = RequestQuery {
+        data: req.encode_vec().into(),
+        path: "/tlcs.timelock.v1beta1.Query/KeypairsByOwner".into(),
+        height: 0,
+        prove: false,
+    };
+
+    let response = app.query(request);
+
+    Ok(Json(
+        QueryAllKeyPairsResponse::decode(response.value)
+            .expect("should be a valid QueryAllKeyPairsResponse"),
+    ))
+}
+
 /// Get all keys for a given round and scheme
 pub async fn get_keypairs_by_round_and_scheme<
     SK: StoreKey,
@@ -212,10 +437,14 @@ pub async fn get_keypairs_by_round_and_scheme<
     G: Genesis,
 >(
     Path((round, scheme)): Path<(u64, u32)>,
-    _pagination: Query<Pagination>,
+    Query(page): Query<PageQuery>,
     State(app): State<BaseApp<SK, PSK, M, BK, AK, H, G>>,
 ) -> Result<Json<QueryAllKeyPairsResponse>, Error> {
-    let req = QueryRoundSchemeRequest { round, scheme };
+    let req = QueryRoundSchemeRequest {
+        round,
+        scheme,
+        pagination: Some(page.try_into()?),
+    };
     let request = RequestQuery {
         data: req.encode_vec().into(),
         path: "/tlcs.timelock.v1beta1.Query/AllKeyPairsByRoundAndScheme".into(),
@@ -241,11 +470,11 @@ pub async fn get_all_loe_data<
     H: Handler<M, SK, G>,
     G: Genesis,
 >(
-    _pagination: Query<Pagination>,
+    Query(page): Query<PageQuery>,
     State(app): State<BaseApp<SK, PSK, M, BK, AK, H, G>>,
 ) -> Result<Json<QueryAllLoeDataResponse>, Error> {
     let request = RequestQuery {
-        data: Bytes::new(),
+        data: PageRequest::try_from(page)?.encode_to_vec().into(),
         path: "/tlcs.timelock.v1beta1.Query/AllLoeData".into(),
         height: 0,
         prove: false,
@@ -270,10 +499,13 @@ pub async fn get_loe_data_by_round<
     G: Genesis,
 >(
     Path(round): Path<u64>,
-    _pagination: Query<Pagination>,
+    Query(page): Query<PageQuery>,
     State(app): State<BaseApp<SK, PSK, M, BK, AK, H, G>>,
 ) -> Result<Json<QueryAllLoeDataResponse>, Error> {
-    let req = QueryRoundRequest { round };
+    let req = QueryRoundRequest {
+        round,
+        pagination: Some(page.try_into()?),
+    };
     let request = RequestQuery {
         data: req.encode_vec().into(),
         path: "/tlcs.timelock.v1beta1.Query/AllLoeDataByRound".into(),
@@ -289,7 +521,7 @@ pub async fn get_loe_data_by_round<
     ))
 }
 
-/// Get all keys for a given round
+/// Get the keypairs waiting for LOE data and the beacons which publish it
 pub async fn get_loe_data_needed<
     SK: StoreKey,
     PSK: ParamsSubspaceKey,
@@ -299,11 +531,11 @@ pub async fn get_loe_data_needed<
     H: Handler<M, SK, G>,
     G: Genesis,
 >(
-    _pagination: Query<Pagination>,
+    Query(page): Query<PageQuery>,
     State(app): State<BaseApp<SK, PSK, M, BK, AK, H, G>>,
-) -> Result<Json<QueryAllKeyPairsResponse>, Error> {
+) -> Result<Json<QueryLoeDataNeededResponse>, Error> {
     let request = RequestQuery {
-        data: Bytes::new(),
+        data: PageRequest::try_from(page)?.encode_to_vec().into(),
         path: "/tlcs.timelock.v1beta1.Query/AllLoeDataNeeded".into(),
         height: 0,
         prove: false,
@@ -312,8 +544,122 @@ pub async fn get_loe_data_needed<
     let response = app.query(request);
 
     Ok(Json(
-        QueryAllKeyPairsResponse::decode(response.value)
-            .expect("should be a valid QueryAllKeyPairsResponse"),
+        QueryLoeDataNeededResponse::decode(response.value)
+            .expect("should be a valid QueryLoeDataNeededResponse"),
+    ))
+}
+
+/// Get the registered beacons
+pub async fn get_beacons<
+    SK: StoreKey,
+    PSK: ParamsSubspaceKey,
+    M: Message,
+    BK: BankKeeper<SK>,
+    AK: AuthKeeper<SK>,
+    H: Handler<M, SK, G>,
+    G: Genesis,
+>(
+    Query(page): Query<PageQuery>,
+    State(app): State<BaseApp<SK, PSK, M, BK, AK, H, G>>,
+) -> Result<Json<QueryBeaconsResponse>, Error> {
+    let request = RequestQuery {
+        data: PageRequest::try_from(page)?.encode_to_vec().into(),
+        path: "/tlcs.timelock.v1beta1.Query/Beacons".into(),
+        height: 0,
+        prove: false,
+    };
+
+    let response = app.query(request);
+
+    Ok(Json(
+        QueryBeaconsResponse::decode(response.value)
+            .expect("should be a valid QueryBeaconsResponse"),
+    ))
+}
+
+/// Get the timelock module params
+pub async fn get_params<
+    SK: StoreKey,
+    PSK: ParamsSubspaceKey,
+    M: Message,
+    BK: BankKeeper<SK>,
+    AK: AuthKeeper<SK>,
+    H: Handler<M, SK, G>,
+    G: Genesis,
+>(
+    State(app): State<BaseApp<SK, PSK, M, BK, AK, H, G>>,
+) -> Result<Json<Params>, Error> {
+    let request = RequestQuery {
+        data: Bytes::new(),
+        path: "/tlcs.timelock.v1beta1.Query/Params".into(),
+        height: 0,
+        prove: false,
+    };
+
+    let response = app.query(request);
+
+    Ok(Json(
+        Params::decode(response.value).expect("should be a valid Params"),
+    ))
+}
+
+/// Get all registered contributors
+pub async fn get_contributors<
+    SK: StoreKey,
+    PSK: ParamsSubspaceKey,
+    M: Message,
+    BK: BankKeeper<SK>,
+    AK: AuthKeeper<SK>,
+    H: Handler<M, SK, G>,
+    G: Genesis,
+>(
+    Query(page): Query<PageQuery>,
+    State(app): State<BaseApp<SK, PSK, M, BK, AK, H, G>>,
+) -> Result<Json<QueryContributorsResponse>, Error> {
+    let request = RequestQuery {
+        data: PageRequest::try_from(page)?.encode_to_vec().into(),
+        path: "/tlcs.timelock.v1beta1.Query/Contributors".into(),
+        height: 0,
+        prove: false,
+    };
+
+    let response = app.query(request);
+
+    Ok(Json(
+        QueryContributorsResponse::decode(response.value)
+            .expect("should be a valid QueryContributorsResponse"),
+    ))
+}
+
+/// Get the participation stats of a contributor
+pub async fn get_contributor_stats<
+    SK: StoreKey,
+    PSK: ParamsSubspaceKey,
+    M: Message,
+    BK: BankKeeper<SK>,
+    AK: AuthKeeper<SK>,
+    H: Handler<M, SK, G>,
+    G: Genesis,
+>(
+    Path(address): Path<AccAddress>,
+    State(app): State<BaseApp<SK, PSK, M, BK, AK, H, G>>,
+) -> Result<Json<QueryContributorStatsResponse>, Error> {
+    let req = QueryAddressRequest {
+        address,
+        pagination: None,
+    };
+    let request = RequestQuery {
+        data: req.encode_vec().into(),
+        path: "/tlcs.timelock.v1beta1.Query/ContributorStats".into(),
+        height: 0,
+        prove: false,
+    };
+
+    let response = app.query(request);
+
+    Ok(Json(
+        QueryContributorStatsResponse::decode(response.value)
+            .expect("should be a valid QueryContributorStatsResponse"),
     ))
 }
 
@@ -322,14 +668,24 @@ async fn endpoint_info() -> &'static str {
      \t /tlcs/timelock/v1beta1/contributions\n\
      \t /tlcs/timelock/v1beta1/contributions_by_round/<round>\n\
      \t /tlcs/timelock/v1beta1/contributions_by_round_and_scheme/<round>/<scheme>\n\
+     \t /tlcs/timelock/v1beta1/contributions_by_keypair/<round>/<scheme>/<id>\n\
      \t /tlcs/timelock/v1beta1/keypairs\n\
      \t /tlcs/timelock/v1beta1/keypairs/round/<round>\n\
      \t /tlcs/timelock/v1beta1/keypairs/time/<time>\n\
+     \t /tlcs/timelock/v1beta1/keypairs/round_range/<from>/<to>\n\
+     \t /tlcs/timelock/v1beta1/keypairs/time_range/<from_unix>/<to_unix>\n\
      \t /tlcs/timelock/v1beta1/keypairs/round_and_scheme/<round>/<scheme>\n\
+     \t /tlcs/timelock/v1beta1/keypairs/<round>/<scheme>/<id>\n\
+     \t /tlcs/timelock/v1beta1/keypairs/owner/<address>\n\
      \t /tlcs/timelock/v1beta1/loe_data\n\
      \t /tlcs/timelock/v1beta1/loe_data/round/<round>\n\
      \t /tlcs/timelock/v1beta1/loe_data_needed\n\
-     \n\t pagination is done by appending offset=<page number>&limit=<rows per page>\n\
+     \t /tlcs/timelock/v1beta1/beacons\n\
+     \t /tlcs/timelock/v1beta1/params\n\
+     \t /tlcs/timelock/v1beta1/contributors\n\
+     \t /tlcs/timelock/v1beta1/contributor_stats/<address>\n\
+     \n\t pagination is done by appending offset=<rows to skip>&limit=<rows per page>\n\
+     \t or page_key=<next_key of the previous page>&limit=<rows per page>\n\
     "
 }
 
@@ -353,14 +709,32 @@ pub fn get_router<
             "/contributions_by_round_and_scheme/:round/:scheme",
             get(get_contributions_by_round_and_scheme),
         )
+        .route(
+            "/contributions_by_keypair/:round/:scheme/:id",
+            get(get_contributions_by_keypair),
+        )
         .route("/keypairs", get(get_all_keypairs))
         .route("/keypairs/round/:round", get(get_keypairs_by_round))
         .route("/keypairs/time/:time", get(get_keypairs_by_time))
+        .route(
+            "/keypairs/round_range/:from/:to",
+            get(get_keypairs_by_round_range),
+        )
+        .route(
+            "/keypairs/time_range/:from_unix/:to_unix",
+            get(get_keypairs_by_time_range),
+        )
         .route(
             "/keypairs/round_and_scheme/:round/:scheme",
             get(get_keypairs_by_round_and_scheme),
         )
+        .route("/keypairs/:round/:scheme/:id", get(get_keypair))
+        .route("/keypairs/owner/:address", get(get_keypairs_by_owner))
         .route("/loe_data", get(get_all_loe_data))
         .route("/loe_data/round/:round", get(get_loe_data_by_round))
         .route("/loe_data_needed", get(get_loe_data_needed))
+        .route("/beacons", get(get_beacons))
+        .route("/params", get(get_params))
+        .route("/contributors", get(get_contributors))
+        .route("/contributor_stats/:address", get(get_contributor_stats))
 }
@@ -0,0 +1,99 @@
+use std::fmt;
+
+use gears::error::AppError;
+
+/// Errors returned by the timelock module. Each has its own ABCI code, which is also included
+/// in the error message, so clients can tell the reasons apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelockError {
+    /// The keypair's contribution window hasn't started yet
+    ContributionWindowNotOpen,
+    /// The keypair's public key time has passed or its public key was already made
+    ContributionWindowClosed,
+    /// The address already contributed to the keypair, stored shares can't be replaced
+    DuplicateContribution,
+    /// The keypair can't move from its current status to the requested one
+    InvalidStatusTransition,
+    /// No keypair is stored for the round, scheme and id
+    KeyPairNotFound,
+    /// Only the address which requested the keypair can do this
+    NotKeyPairOwner,
+    /// No beacon is registered with the id
+    BeaconNotFound,
+    /// The beacon is retired, new keypairs can't be requested for it
+    BeaconRetired,
+    /// Keys can't be timelocked to the rounds of the beacon's scheme
+    BeaconNotTimelockable,
+    /// Only registered contributors which aren't jailed can contribute
+    NotRegisteredContributor,
+    /// The keyshare isn't valid for the keypair's round and beacon
+    InvalidKeyshare,
+    /// Canonical keypairs are shared by every address which requested them, so none of them
+    /// can cancel it
+    CanonicalKeyPairNotCancellable,
+}
+
+impl TimelockError {
+    /// ABCI code of the error. The codes start at 101 so they don't collide with the codes of
+    /// the gears errors.
+    pub fn code(&self) -> u32 {
+        match self {
+            TimelockError::ContributionWindowNotOpen => 101,
+            TimelockError::ContributionWindowClosed => 102,
+            TimelockError::DuplicateContribution => 103,
+            TimelockError::InvalidStatusTransition => 104,
+            TimelockError::KeyPairNotFound => 105,
+            TimelockError::NotKeyPairOwner => 106,
+            TimelockError::BeaconNotFound => 107,
+            TimelockError::BeaconRetired => 108,
+            TimelockError::BeaconNotTimelockable => 109,
+            TimelockError::NotRegisteredContributor => 110,
+            TimelockError::InvalidKeyshare => 111,
+            TimelockError::CanonicalKeyPairNotCancellable => 112,
+        }
+    }
+}
+
+impl fmt::Display for TimelockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            TimelockError::ContributionWindowNotOpen => {
+                "the contribution window for the keypair is not open yet"
+            }
+            TimelockError::ContributionWindowClosed => {
+                "the contribution window for the keypair is closed"
+            }
+            TimelockError::DuplicateContribution => {
+                "the address already contributed to the keypair"
+            }
+            TimelockError::InvalidStatusTransition => {
+                "the keypair can't move to the requested status"
+            }
+            TimelockError::KeyPairNotFound => "the keypair doesn't exist",
+            TimelockError::NotKeyPairOwner => "the address didn't request the keypair",
+            TimelockError::BeaconNotFound => "the beacon isn't registered",
+            TimelockError::BeaconRetired => "the beacon is retired",
+            TimelockError::BeaconNotTimelockable => {
+                "keys can't be timelocked to the beacon's scheme"
+            }
+            TimelockError::NotRegisteredContributor => {
+                "the address isn't an active registered contributor"
+            }
+            TimelockError::InvalidKeyshare => "the keyshare is invalid for the keypair's round",
+            TimelockError::CanonicalKeyPairNotCancellable => {
+                "canonical keypairs are shared and can't be cancelled"
+            }
+        };
+
+        write!(f, "timelock error {}: {}", self.code(), msg)
+    }
+}
+
+impl From<TimelockError> for AppError {
+    fn from(e: TimelockError) -> Self {
+        AppError::Custom {
+            code: e.code(),
+            log: e.to_string(),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use proto_types::AccAddress;
+use tendermint_proto::abci::{Event, EventAttribute};
+
+use crate::proto::tlcs::v1beta1::RawMsgKeyPair;
+
+/// Identifies the keypair an event is about. The owner is empty for keypairs which were
+/// requested before the requester was recorded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyPairRef {
+    pub round: u64,
+    pub scheme: u32,
+    pub id: u32,
+    pub owner: String,
+}
+
+impl From<&RawMsgKeyPair> for KeyPairRef {
+    fn from(keypair: &RawMsgKeyPair) -> Self {
+        KeyPairRef {
+            round: keypair.round,
+            scheme: keypair.scheme,
+            id: keypair.id,
+            owner: keypair.owner.clone(),
+        }
+    }
+}
+
+/// Events emitted by the timelock module. All attributes are indexed so clients can
+/// subscribe to them, e.g. `tm.event='NewBlock' AND public_key_finalized.owner='cosmos1...'`.
+/// Event types are named for what happened, in the past tense. Events of a failed tx are
+/// discarded, so a rejected contribution is reported by the code and log of its tx instead.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimelockEvent {
+    KeyRequestOpened(KeyPairRef),
+    KeyRequestCancelled(KeyPairRef),
+    /// A canonical keypair was shared with another requester, the keypair is the shared one
+    CanonicalKeyPairShared {
+        keypair: KeyPairRef,
+        requester: AccAddress,
+    },
+    ContributionAccepted {
+        keypair: KeyPairRef,
+        contributor: AccAddress,
+    },
+    PublicKeyFinalized(KeyPairRef),
+    /// The public key time passed without enough contributions
+    KeyPairFailed(KeyPairRef),
+    LoeDataStored {
+        beacon_id: u32,
+        round: u64,
+    },
+    SecretKeyReleased(KeyPairRef),
+    ParamsUpdateScheduled {
+        authority: AccAddress,
+        height: u64,
+    },
+    ParamsUpdated {
+        height: u64,
+    },
+    ContributorRegistered {
+        address: AccAddress,
+    },
+    ContributorDeregistered {
+        address: AccAddress,
+    },
+    ContributorJailed {
+        address: String,
+    },
+    BeaconUpdated {
+        id: u32,
+        chain_hash: String,
+        retired: bool,
+    },
+}
+
+impl TimelockEvent {
+    /// The event type clients subscribe to
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TimelockEvent::KeyRequestOpened(_) => "key_request_opened",
+            TimelockEvent::KeyRequestCancelled(_) => "key_request_cancelled",
+            TimelockEvent::CanonicalKeyPairShared { .. } => "canonical_keypair_shared",
+            TimelockEvent::ContributionAccepted { .. } => "contribution_accepted",
+            TimelockEvent::PublicKeyFinalized(_) => "public_key_finalized",
+            TimelockEvent::KeyPairFailed(_) => "keypair_failed",
+            TimelockEvent::LoeDataStored { .. } => "loe_data_stored",
+            TimelockEvent::SecretKeyReleased(_) => "secret_key_released",
+            TimelockEvent::ParamsUpdateScheduled { .. } => "params_update_scheduled",
+            TimelockEvent::ParamsUpdated { .. } => "params_updated",
+            TimelockEvent::ContributorRegistered { .. } => "contributor_registered",
+            TimelockEvent::ContributorDeregistered { .. } => "contributor_deregistered",
+            TimelockEvent::ContributorJailed { .. } => "contributor_jailed",
+            TimelockEvent::BeaconUpdated { .. } => "beacon_updated",
+        }
+    }
+
+    fn attributes(&self) -> Vec<(&'static str, String)> {
+        match self {
+            TimelockEvent::KeyRequestOpened(keypair)
+            | TimelockEvent::KeyRequestCancelled(keypair)
+            | TimelockEvent::PublicKeyFinalized(keypair)
+            | TimelockEvent::KeyPairFailed(keypair)
+            | TimelockEvent::SecretKeyReleased(keypair) => keypair_attributes(keypair),
+            TimelockEvent::CanonicalKeyPairShared { keypair, requester } => {
+                let mut attributes = keypair_attributes(keypair);
+                attributes.push(("requester", requester.to_string()));
+                attributes
+            }
+            TimelockEvent::ContributionAccepted {
+                keypair,
+                contributor,
+            } => {
+                let mut attributes = keypair_attributes(keypair);
+                attributes.push(("contributor", contributor.to_string()));
+                attributes
+            }
+            TimelockEvent::LoeDataStored { beacon_id, round } => vec![
+                ("beacon_id", beacon_id.to_string()),
+                ("round", round.to_string()),
+            ],
+            TimelockEvent::ParamsUpdateScheduled { authority, height } => vec![
+                ("authority", authority.to_string()),
+                ("height", height.to_string()),
+            ],
+            TimelockEvent::ParamsUpdated { height } => vec![("height", height.to_string())],
+            TimelockEvent::ContributorRegistered { address }
+            | TimelockEvent::ContributorDeregistered { address } => {
+                vec![("address", address.to_string())]
+            }
+            TimelockEvent::ContributorJailed { address } => vec![("address", address.clone())],
+            TimelockEvent::BeaconUpdated {
+                id,
+                chain_hash,
+                retired,
+            } => vec![
+                ("id", id.to_string()),
+                ("chain_hash", chain_hash.clone()),
+                ("retired", retired.to_string()),
+            ],
+        }
+    }
+}
+
+fn keypair_attributes(keypair: &KeyPairRef) -> Vec<(&'static str, String)> {
+    vec![
+        ("round", keypair.round.to_string()),
+        ("scheme", keypair.scheme.to_string()),
+        ("id", keypair.id.to_string()),
+        ("owner", keypair.owner.clone()),
+    ]
+}
+
+impl From<TimelockEvent> for Event {
+    fn from(event: TimelockEvent) -> Self {
+        Event {
+            r#type: event.kind().into(),
+            attributes: event
+                .attributes()
+                .into_iter()
+                .map(|(key, value)| EventAttribute {
+                    key: key.into(),
+                    value: value.into(),
+                    index: true,
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::BTreeMap;
+
+use ibc_proto::protobuf::Protobuf;
+use prost::Message;
+use proto_types::AccAddress;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    default_beacons,
+    keeper::valid_scheme,
+    proto::tlcs::v1beta1::{
+        KeyPairStatus, RawArchivedKeyPair, RawBeacon, RawContributor, RawContributorStats,
+        RawFeeEscrow, RawMsgContribution, RawMsgKeyPair, RawMsgLoeData,
+    },
+    registered_beacon, validate_beacon, Params, RawParams,
+};
+
+/// Genesis state of the timelock module. This is also what is returned when
+/// the module state is exported, so it can be used to restart or fork a chain.
+#[derive(Serialize, Deserialize, Clone, Message)]
+#[serde(deny_unknown_fields)]
+pub struct GenesisState {
+    #[prost(message, repeated, tag = "1")]
+    pub keypairs: Vec<RawMsgKeyPair>,
+    #[prost(message, repeated, tag = "2")]
+    pub contributions: Vec<RawMsgContribution>,
+    #[prost(message, repeated, tag = "3")]
+    pub loe_data: Vec<RawMsgLoeData>,
+    #[prost(message, optional, tag = "4")]
+    pub params: Option<RawParams>,
+    /// Address which is allowed to update the params. If empty the params can't be updated.
+    #[prost(string, tag = "5")]
+    pub authority: String,
+    #[prost(message, repeated, tag = "6")]
+    pub contributors: Vec<RawContributor>,
+    #[prost(message, repeated, tag = "7")]
+    pub fee_escrows: Vec<RawFeeEscrow>,
+    #[prost(message, repeated, tag = "8")]
+    pub archived_keypairs: Vec<RawArchivedKeyPair>,
+    /// Registered beacons. If empty the default beacons are registered.
+    #[prost(message, repeated, tag = "9")]
+    pub beacons: Vec<RawBeacon>,
+    /// Participation counts of the addresses which were ever registered contributors
+    #[prost(message, repeated, tag = "10")]
+    pub contributor_stats: Vec<RawContributorStats>,
+}
+
+impl GenesisState {
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.params {
+            Some(params) => Params::try_from(params.to_owned())
+                .map_err(|e| e.to_string())?
+                .validate()?,
+            None => return Err("params must be set".into()),
+        };
+
+        if !self.authority.is_empty() {
+            AccAddress::from_bech32(&self.authority)
+                .map_err(|e| format!("invalid authority address: {}", e))?;
+        }
+
+        let beacons = if self.beacons.is_empty() {
+            default_beacons()
+        } else {
+            self.beacons.clone()
+        };
+
+        let mut beacon_ids = vec![];
+
+        for beacon in &self.beacons {
+            validate_beacon(beacon)?;
+
+            if beacon_ids.contains(&beacon.id) {
+                return Err(format!("duplicate beacon: {}", beacon.id));
+            }
+            beacon_ids.push(beacon.id);
+        }
+
+        let mut contributor_addresses = vec![];
+
+        for contributor in &self.contributors {
+            AccAddress::from_bech32(&contributor.address)
+                .map_err(|e| format!("invalid contributor address: {}", e))?;
+
+            if contributor_addresses.contains(&contributor.address) {
+                return Err(format!("duplicate contributor: {}", contributor.address));
+            }
+            contributor_addresses.push(contributor.address.clone());
+        }
+
+        let mut stats_addresses = vec![];
+
+        for stats in &self.contributor_stats {
+            AccAddress::from_bech32(&stats.address)
+                .map_err(|e| format!("invalid contributor stats address: {}", e))?;
+
+            if stats_addresses.contains(&stats.address) {
+                return Err(format!("duplicate contributor stats: {}", stats.address));
+            }
+            stats_addresses.push(stats.address.clone());
+        }
+
+        let mut keypair_ids = vec![];
+
+        for keypair in &self.keypairs {
+            if !valid_scheme(keypair.scheme) {
+                return Err(format!(
+                    "keypair has invalid scheme. Round: {}, Scheme: {}",
+                    keypair.round, keypair.scheme
+                ));
+            }
+
+            let status = match KeyPairStatus::from_i32(keypair.status) {
+                Some(status) => status,
+                None => {
+                    return Err(format!(
+                        "keypair has invalid status. Round: {}, Scheme: {}, Id: {}",
+                        keypair.round, keypair.scheme, keypair.id
+                    ))
+                }
+            };
+
+            // The status must agree with what the keypair was made from and what was made of it
+            let has_contributions = self.contributions.iter().any(|contribution| {
+                (contribution.round, contribution.scheme, contribution.id)
+                    == (keypair.round, keypair.scheme, keypair.id)
+            });
+            let has_public_key = !keypair.public_key.is_empty();
+            let has_private_key = !keypair.private_key.is_empty();
+            let consistent = match status {
+                KeyPairStatus::Requested => !has_contributions && !has_public_key,
+                KeyPairStatus::Collecting => has_contributions && !has_public_key,
+                KeyPairStatus::PublicKeyReady | KeyPairStatus::AwaitingBeacon => {
+                    has_contributions && has_public_key
+                }
+                KeyPairStatus::Released => has_public_key && has_private_key,
+                KeyPairStatus::Failed | KeyPairStatus::Cancelled => !has_public_key,
+            };
+            if !consistent || (has_private_key && status != KeyPairStatus::Released) {
+                return Err(format!(
+                    "keypair status {:?} doesn't match its contributions and keys. Round: {}, Scheme: {}, Id: {}",
+                    status, keypair.round, keypair.scheme, keypair.id
+                ));
+            }
+
+            match beacons.iter().find(|beacon| beacon.id == keypair.beacon_id) {
+                None => {
+                    return Err(format!(
+                        "keypair has unknown beacon. Round: {}, Scheme: {}, Id: {}",
+                        keypair.round, keypair.scheme, keypair.id
+                    ))
+                }
+                Some(beacon)
+                    if !registered_beacon(beacon).map_or(false, |b| b.supports_timelock()) =>
+                {
+                    return Err(format!(
+                        "keypair has a beacon keys can't be timelocked to. Round: {}, Scheme: {}, Id: {}",
+                        keypair.round, keypair.scheme, keypair.id
+                    ))
+                }
+                Some(_) => {}
+            }
+
+            if !keypair.owner.is_empty() {
+                AccAddress::from_bech32(&keypair.owner)
+                    .map_err(|e| format!("invalid keypair owner address: {}", e))?;
+            }
+
+            let keypair_id = (keypair.round, keypair.scheme, keypair.id);
+            if keypair_ids.contains(&keypair_id) {
+                return Err(format!(
+                    "duplicate keypair. Round: {}, Scheme: {}, Id: {}",
+                    keypair.round, keypair.scheme, keypair.id
+                ));
+            }
+            keypair_ids.push(keypair_id);
+        }
+
+        for loe_data in &self.loe_data {
+            if !beacons.iter().any(|beacon| beacon.id == loe_data.beacon_id) {
+                return Err(format!(
+                    "loe data has unknown beacon. Beacon: {}, Round: {}",
+                    loe_data.beacon_id, loe_data.round
+                ));
+            }
+        }
+
+        let mut contribution_ids = vec![];
+
+        for contribution in &self.contributions {
+            let contribution_id = (
+                contribution.round,
+                contribution.scheme,
+                contribution.id,
+                contribution.address.clone(),
+            );
+            if contribution_ids.contains(&contribution_id) {
+                return Err(format!(
+                    "duplicate contribution from {}. Round: {}, Scheme: {}, Id: {}",
+                    contribution.address, contribution.round, contribution.scheme, contribution.id
+                ));
+            }
+            contribution_ids.push(contribution_id);
+
+            if !keypair_ids.contains(&(contribution.round, contribution.scheme, contribution.id)) {
+                return Err(format!(
+                    "contribution from {} refers to a missing keypair. Round: {}, Scheme: {}, Id: {}",
+                    contribution.address, contribution.round, contribution.scheme, contribution.id
+                ));
+            }
+        }
+
+        // Fees which couldn't be paid stay escrowed, also after their keypair is archived
+        for escrow in &self.fee_escrows {
+            AccAddress::from_bech32(&escrow.payer)
+                .map_err(|e| format!("invalid fee escrow payer address: {}", e))?;
+
+            let escrow_id = (escrow.round, escrow.scheme, escrow.id);
+            if !keypair_ids.contains(&escrow_id)
+                && !self
+                    .archived_keypairs
+                    .iter()
+                    .any(|archived| (archived.round, archived.scheme, archived.id) == escrow_id)
+            {
+                return Err(format!(
+                    "fee escrow refers to a missing keypair. Round: {}, Scheme: {}, Id: {}",
+                    escrow.round, escrow.scheme, escrow.id
+                ));
+            }
+        }
+
+        for archived in &self.archived_keypairs {
+            if keypair_ids.contains(&(archived.round, archived.scheme, archived.id)) {
+                return Err(format!(
+                    "keypair is both archived and stored. Round: {}, Scheme: {}, Id: {}",
+                    archived.round, archived.scheme, archived.id
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the amount of each denom which the timelock module account must hold at genesis,
+    /// the bonds of the contributors and the escrowed request fees. They are paid out of the
+    /// module account on deregistration and when the escrows are released.
+    pub fn module_holdings(&self) -> Result<BTreeMap<String, u64>, String> {
+        let bonds = self
+            .contributors
+            .iter()
+            .map(|contributor| (&contributor.bond_denom, contributor.bond));
+        let fees = self
+            .fee_escrows
+            .iter()
+            .map(|escrow| (&escrow.denom, escrow.amount));
+
+        let mut holdings: BTreeMap<String, u64> = BTreeMap::new();
+        for (denom, amount) in bonds.chain(fees).filter(|(_, amount)| *amount > 0) {
+            let total = holdings.entry(denom.clone()).or_default();
+            *total = total
+                .checked_add(amount)
+                .ok_or_else(|| format!("module holdings of {} overflow", denom))?;
+        }
+
+        Ok(holdings)
+    }
+}
+
+impl Protobuf<GenesisState> for GenesisState {}
@@ -1,5 +1,9 @@
 use database::Database;
-use gears::{error::AppError, types::context::TxContext};
+use gears::{
+    error::AppError,
+    types::context::{InitContext, TxContext},
+    x::params::ParamsSubspaceKey,
+};
 use ibc_proto::protobuf::Protobuf;
 use prost::Message as ProstMessage;
 use store::StoreKey;
@@ -7,18 +11,21 @@ use tendermint_proto::abci::RequestBeginBlock;
 //use tracing::info;
 
 use crate::{
-    proto::tlcs::v1beta1::{QueryRoundRequest, QueryRoundSchemeRequest, QueryTimeRequest},
-    Config, Keeper, Message,
+    proto::tlcs::v1beta1::{
+        PageRequest, QueryAddressRequest, QueryKeyPairRequest, QueryRoundRangeRequest,
+        QueryRoundRequest, QueryRoundSchemeRequest, QueryTimeRangeRequest, QueryTimeRequest,
+    },
+    Config, GenesisState, Keeper, Message,
 };
 
 #[derive(Debug, Clone)]
-pub struct Handler<SK: StoreKey> {
-    keeper: Keeper<SK>,
+pub struct Handler<SK: StoreKey, PSK: ParamsSubspaceKey> {
+    keeper: Keeper<SK, PSK>,
     config: Config,
 }
 
-impl<SK: StoreKey> Handler<SK> {
-    pub fn new(keeper: Keeper<SK>, config: Config) -> Self {
+impl<SK: StoreKey, PSK: ParamsSubspaceKey> Handler<SK, PSK> {
+    pub fn new(keeper: Keeper<SK, PSK>, config: Config) -> Self {
         Handler { keeper, config }
     }
 
@@ -31,23 +38,31 @@ impl<SK: StoreKey> Handler<SK> {
             Message::NewProcess(msg) => self.keeper.open_new_process(ctx, msg),
             Message::MultiNewProcess(msg) => self.keeper.open_multi_new_process(ctx, msg),
             Message::Participate(msg) => self.keeper.append_contribution(ctx, msg),
-            Message::SubmitLoeData(msg) => self.keeper.append_loe_data(&mut ctx.as_any(), msg),
+            Message::SubmitLoeData(msg) => self.keeper.append_loe_data(ctx, msg),
+            Message::UpdateParams(msg) => self.keeper.update_params(ctx, msg),
+            Message::RegisterContributor(msg) => self.keeper.register_contributor(ctx, msg),
+            Message::DeregisterContributor(msg) => self.keeper.deregister_contributor(ctx, msg),
+            Message::CancelKeyRequest(msg) => self.keeper.cancel_key_request(ctx, msg),
+            Message::UpdateBeacon(msg) => self.keeper.update_beacon(ctx, msg),
         }
     }
 
+    pub fn init_genesis<DB: Database>(&self, ctx: &mut InitContext<DB, SK>, genesis: GenesisState) {
+        self.keeper.init_genesis(ctx, genesis)
+    }
+
     pub fn handle_begin_block<DB: Database>(
         &self,
         ctx: &mut TxContext<DB, SK>,
         _request: RequestBeginBlock,
     ) {
-        // TODO: Get this from the number of validators. For now we'll just set it here
-        //let _ = set_contribution_threshold(ctx, 2);
-        //let contribution_threshold = get_contribution_threshold(ctx);
+        self.keeper.migrate_store(ctx);
+        self.keeper.apply_params_updates(ctx);
 
-        let contribution_threshold: u32 = 2;
-        let block_time = ctx.get_header().time.unix_timestamp();
+        let contribution_threshold = self.keeper.contribution_threshold(ctx);
+        let block_time = self.keeper.get_block_time(ctx);
 
-        let (need_pub_keys, need_secret_keys) = self.keeper.get_empty_keypairs(ctx);
+        let (need_pub_keys, need_secret_keys) = self.keeper.get_empty_keypairs(ctx, block_time);
 
         //info!("BEGINBLOCKER: need pubkeys: {:?}", need_pub_keys.len());
         self.keeper
@@ -56,6 +71,8 @@ impl<SK: StoreKey> Handler<SK> {
         //info!( "BEGINBLOCKER: need secret keys: {:?}", need_secret_keys.len());
         self.keeper.make_secret_keys(ctx, need_secret_keys);
 
+        self.keeper.prune_final_keypairs(ctx);
+
         let _ = self.keeper.make_keyshares(ctx, self.config.clone());
     }
 
@@ -65,18 +82,22 @@ impl<SK: StoreKey> Handler<SK> {
         query: tendermint_proto::abci::RequestQuery,
     ) -> std::result::Result<bytes::Bytes, AppError> {
         match query.path.as_str() {
-            "/tlcs.timelock.v1beta1.Query/AllContributions" => Ok(self
-                .keeper
-                .query_all_contributions(ctx)
-                .encode_to_vec()
-                .into()),
+            "/tlcs.timelock.v1beta1.Query/AllContributions" => {
+                let req = decode_page_request(query.data.clone())?;
+
+                Ok(self
+                    .keeper
+                    .query_all_contributions(ctx, Some(req))
+                    .encode_to_vec()
+                    .into())
+            }
             "/tlcs.timelock.v1beta1.Query/AllContributionsByRound" => {
                 let data = query.data.clone();
                 let req = QueryRoundRequest::decode(data)?;
 
                 Ok(self
                     .keeper
-                    .query_contributions_by_round(ctx, req.round)
+                    .query_contributions_by_round(ctx, req.round, req.pagination)
                     .encode_to_vec()
                     .into())
             }
@@ -86,12 +107,39 @@ impl<SK: StoreKey> Handler<SK> {
 
                 Ok(self
                     .keeper
-                    .query_contributions_by_round_and_scheme(ctx, req.round, req.scheme)
+                    .query_contributions_by_round_and_scheme(
+                        ctx,
+                        req.round,
+                        req.scheme,
+                        req.pagination,
+                    )
+                    .encode_to_vec()
+                    .into())
+            }
+            "/tlcs.timelock.v1beta1.Query/AllContributionsByKeyPair" => {
+                let data = query.data.clone();
+                let req = QueryKeyPairRequest::decode(data)?;
+
+                Ok(self
+                    .keeper
+                    .query_contributions_by_keypair(
+                        ctx,
+                        req.round,
+                        req.scheme,
+                        req.id,
+                        req.pagination,
+                    )
                     .encode_to_vec()
                     .into())
             }
             "/tlcs.timelock.v1beta1.Query/AllKeyPairs" => {
-                Ok(self.keeper.query_all_keypairs(ctx).encode_to_vec().into())
+                let req = decode_page_request(query.data.clone())?;
+
+                Ok(self
+                    .keeper
+                    .query_all_keypairs(ctx, Some(req))
+                    .encode_to_vec()
+                    .into())
             }
             "/tlcs.timelock.v1beta1.Query/AllKeyPairsByRound" => {
                 let data = query.data.clone();
@@ -99,7 +147,27 @@ impl<SK: StoreKey> Handler<SK> {
 
                 Ok(self
                     .keeper
-                    .query_keypairs_by_round(ctx, req.round)
+                    .query_keypairs_by_round(ctx, req.round, req.pagination)
+                    .encode_to_vec()
+                    .into())
+            }
+            "/tlcs.timelock.v1beta1.Query/KeyPair" => {
+                let data = query.data.clone();
+                let req = QueryKeyPairRequest::decode(data)?;
+
+                Ok(self
+                    .keeper
+                    .query_keypair(ctx, req.round, req.scheme, req.id)?
+                    .encode_to_vec()
+                    .into())
+            }
+            "/tlcs.timelock.v1beta1.Query/KeypairsByOwner" => {
+                let data = query.data.clone();
+                let req = QueryAddressRequest::decode(data)?;
+
+                Ok(self
+                    .keeper
+                    .query_keypairs_by_owner(ctx, req.address, req.pagination)
                     .encode_to_vec()
                     .into())
             }
@@ -109,7 +177,27 @@ impl<SK: StoreKey> Handler<SK> {
 
                 Ok(self
                     .keeper
-                    .query_keypairs_by_round_and_scheme(ctx, req.round, req.scheme)
+                    .query_keypairs_by_round_and_scheme(ctx, req.round, req.scheme, req.pagination)
+                    .encode_to_vec()
+                    .into())
+            }
+            "/tlcs.timelock.v1beta1.Query/KeypairsByRoundRange" => {
+                let data = query.data.clone();
+                let req = QueryRoundRangeRequest::decode(data)?;
+
+                Ok(self
+                    .keeper
+                    .query_keypairs_by_round_range(ctx, req.from, req.to, req.pagination)
+                    .encode_to_vec()
+                    .into())
+            }
+            "/tlcs.timelock.v1beta1.Query/KeypairsByTimeRange" => {
+                let data = query.data.clone();
+                let req = QueryTimeRangeRequest::decode(data)?;
+
+                Ok(self
+                    .keeper
+                    .query_keypairs_by_time_range(ctx, req.from_unix, req.to_unix, req.pagination)
                     .encode_to_vec()
                     .into())
             }
@@ -119,12 +207,18 @@ impl<SK: StoreKey> Handler<SK> {
 
                 Ok(self
                     .keeper
-                    .query_keypairs_by_time(ctx, req.time)
+                    .query_keypairs_by_time(ctx, req.time, req.pagination)
                     .encode_to_vec()
                     .into())
             }
             "/tlcs.timelock.v1beta1.Query/AllLoeData" => {
-                Ok(self.keeper.query_all_loe_data(ctx).encode_to_vec().into())
+                let req = decode_page_request(query.data.clone())?;
+
+                Ok(self
+                    .keeper
+                    .query_all_loe_data(ctx, Some(req))
+                    .encode_to_vec()
+                    .into())
             }
             "/tlcs.timelock.v1beta1.Query/AllLoeDataByRound" => {
                 let data = query.data.clone();
@@ -132,16 +226,75 @@ impl<SK: StoreKey> Handler<SK> {
 
                 Ok(self
                     .keeper
-                    .query_loe_data_by_round(ctx, req.round)
+                    .query_loe_data_by_round(ctx, req.round, req.pagination)
+                    .encode_to_vec()
+                    .into())
+            }
+            "/tlcs.timelock.v1beta1.Query/AllLoeDataNeeded" => {
+                let req = decode_page_request(query.data.clone())?;
+
+                Ok(self
+                    .keeper
+                    .query_loe_data_needed(ctx, Some(req))
+                    .encode_to_vec()
+                    .into())
+            }
+            "/tlcs.timelock.v1beta1.Query/Beacons" => {
+                let req = decode_page_request(query.data.clone())?;
+
+                Ok(self
+                    .keeper
+                    .query_beacons(ctx, Some(req))
                     .encode_to_vec()
                     .into())
             }
-            "/tlcs.timelock.v1beta1.Query/AllLoeDataNeeded" => Ok(self
-                .keeper
-                .query_loe_data_needed(ctx)
-                .encode_to_vec()
-                .into()),
+            "/tlcs.timelock.v1beta1.Query/Params" => {
+                Ok(self.keeper.query_params(ctx).encode_vec().into())
+            }
+            "/tlcs.timelock.v1beta1.Query/Contributors" => {
+                let req = decode_page_request(query.data.clone())?;
+
+                Ok(self
+                    .keeper
+                    .query_contributors(ctx, Some(req))
+                    .encode_vec()
+                    .into())
+            }
+            "/tlcs.timelock.v1beta1.Query/ContributorStats" => {
+                let data = query.data.clone();
+                let req = QueryAddressRequest::decode(data)?;
+
+                Ok(self
+                    .keeper
+                    .query_contributor_stats(ctx, req.address)
+                    .encode_vec()
+                    .into())
+            }
+            "/tlcs.timelock.v1beta1.Query/PruneStats" => {
+                Ok(self.keeper.query_prune_stats(ctx).encode_vec().into())
+            }
+            "/tlcs.timelock.v1beta1.Query/PendingWork" => {
+                Ok(self.keeper.query_pending_work(ctx).encode_vec().into())
+            }
+            "/tlcs.timelock.v1beta1.Query/AllArchivedKeyPairs" => {
+                let req = decode_page_request(query.data.clone())?;
+
+                Ok(self
+                    .keeper
+                    .query_archived_keypairs(ctx, Some(req))
+                    .encode_vec()
+                    .into())
+            }
+            "/tlcs.timelock.v1beta1.Query/ExportGenesis" => {
+                Ok(self.keeper.export_genesis(ctx).encode_to_vec().into())
+            }
             _ => Err(AppError::InvalidRequest("query path not found".into())),
         }
     }
 }
+
+// The list queries without other parameters take just the page request. An empty request gets
+// the first page.
+fn decode_page_request(data: bytes::Bytes) -> Result<PageRequest, AppError> {
+    PageRequest::decode(data).map_err(|e| AppError::InvalidRequest(e.to_string()))
+}
@@ -1,15 +1,21 @@
 use gears::client::keys::key_store::DiskStore;
 use ibc_relayer::keyring::SigningKeyPair;
-use std::{collections::BTreeMap, collections::HashMap, thread};
+use std::{collections::BTreeMap, thread};
 
 use bytes::Bytes;
 use database::{Database, PrefixDB};
 use gears::{
     error::AppError,
-    types::context::{Context, QueryContext, TxContext},
+    types::context::{InitContext, QueryContext, TxContext},
+    x::params::{Keeper as ParamsKeeper, ParamsSubspaceKey},
 };
 use prost::Message;
-use store::{MutablePrefixStore, StoreKey};
+use proto_messages::cosmos::{
+    bank::v1beta1::MsgSend,
+    base::v1beta1::{Coin, SendCoins},
+};
+use proto_types::Denom;
+use store::{ImmutablePrefixStore, MutablePrefixStore, StoreKey};
 use tracing::info;
 // Include to run benchmark and uncomment benchmark in test
 //use std::time::Instant;
@@ -17,41 +23,73 @@ use ibc_relayer::keyring::Secp256k1KeyPair;
 use proto_types::AccAddress;
 use std::str::FromStr;
 use tlcs_rust::chain_functions::{
-    loe_signature_is_valid, make_keyshare, make_public_key, make_secret_key, verify_keyshare,
+    make_keyshare, make_public_key, make_secret_key, verify_keyshare,
 };
 
 use crate::{
+    default_beacons,
     proto::tlcs::v1beta1::{
+        KeyPairStatus,
+        MsgCancelKeyRequest,
         MsgContribution,
+        MsgDeregisterContributor,
         MsgKeyPair,
         MsgLoeData,
         MsgMultiNewProcess,
         MsgNewProcess,
+        MsgRegisterContributor,
+        MsgUpdateBeacon,
+        MsgUpdateParams,
+        PageRequest,
+        PageResponse,
         QueryAllContributionsResponse,
         QueryAllKeyPairsResponse,
         QueryAllLoeDataResponse,
+        QueryArchivedKeyPairsResponse,
+        QueryBeaconsResponse,
+        QueryContributorStatsResponse,
+        QueryContributorsResponse,
+        QueryKeyPairResponse,
+        QueryLoeDataNeededResponse,
+        QueryPendingWorkResponse,
+        QueryPruneStatsResponse,
+        RawArchivedKeyPair,
+        RawBeacon,
+        RawContributor,
+        RawContributorStats,
+        RawFeeEscrow,
+        RawKeyPairStatusChange,
         RawMsgContribution,
         RawMsgKeyPair,
         RawMsgLoeData,
+        RawMsgUpdateParams,
         //RawMsgNewProcess,
     },
+    registered_beacon,
     utils::run_tx_command,
-    Config,
+    validate_beacon, Beacon, Config, GenesisState, Params, TimelockError, TimelockEvent,
+    TimelockParamsKeeper, QUICKNET_BEACON_ID, TIMELOCK_MODULE_ADDRESS,
 };
 use anyhow::Result;
 
-use chrono::Utc;
-
-use crate::LOE_GENESIS_TIME;
-use crate::LOE_PERIOD;
-use crate::LOE_PUBLIC_KEY;
-use crate::SECURITY_PARAM;
-
 // Key Prefixes
-use crate::CONTRIBUTION_THRESHOLD_KEY;
+use crate::ARCHIVED_KEYPAIR_KEY;
+use crate::AUTHORITY_KEY;
+use crate::BEACON_KEY;
+use crate::CONTRIBUTOR_DATA_KEY;
+use crate::FEE_ESCROW_KEY;
 use crate::KEYPAIR_DATA_KEY;
 use crate::LOE_DATA_KEY;
+use crate::LOE_QUEUE_KEY;
+use crate::OWNER_INDEX_KEY;
 use crate::PARTICIPANT_DATA_KEY;
+use crate::PARTICIPATION_KEY;
+use crate::PENDING_PARAMS_KEY;
+use crate::PENDING_WORK_KEY;
+use crate::PRUNE_STATS_KEY;
+use crate::PUBKEY_QUEUE_KEY;
+use crate::RELEASED_QUEUE_KEY;
+use crate::STORE_VERSION_KEY;
 
 // Temporary function to convert the scheme type number into string for the tlcs-rust code
 pub fn scheme_to_string(scheme: u32) -> String {
@@ -75,22 +113,1162 @@ pub fn all_schemes_valid(schemes: Vec<u32>) -> bool {
     true
 }
 
-pub fn check_time(time: i64) -> bool {
-    let now = Utc::now();
-    time > now.timestamp()
+/// Checks that the requested public key time is far enough ahead of the block time. This must
+/// only depend on chain data, using the local clock would let validators disagree on the result.
+pub fn check_time(time: i64, block_time: i64, min_lead_time: u32) -> bool {
+    time > block_time && time - block_time >= min_lead_time as i64
+}
+
+/// Version of the store layout. Version 0 is the layout of the first release, which stored
+/// rounds, schemes and ids little endian and had no params, beacons or queues.
+const STORE_VERSION: u64 = 1;
+
+/// Key under which the first release stored the contribution threshold
+const LEGACY_CONTRIBUTION_THRESHOLD_KEY: [u8; 1] = [0];
+
+fn keypair_store_key(round: u64, scheme: u32, id: u32) -> Vec<u8> {
+    let mut store_key = KEYPAIR_DATA_KEY.to_vec();
+    store_key.append(&mut round.to_be_bytes().to_vec());
+    store_key.append(&mut scheme.to_be_bytes().to_vec());
+    store_key.append(&mut id.to_be_bytes().to_vec());
+    store_key
+}
+
+fn keypair_contributions_prefix(round: u64, scheme: u32, id: u32) -> Vec<u8> {
+    let mut store_key = PARTICIPANT_DATA_KEY.to_vec();
+    store_key.append(&mut round.to_be_bytes().to_vec());
+    store_key.append(&mut scheme.to_be_bytes().to_vec());
+    store_key.append(&mut id.to_be_bytes().to_vec());
+    store_key
+}
+
+fn contribution_store_key(round: u64, scheme: u32, id: u32, address: AccAddress) -> Vec<u8> {
+    let mut store_key = keypair_contributions_prefix(round, scheme, id);
+    let addr: Vec<u8> = address.into();
+    store_key.append(&mut addr.to_vec());
+    store_key
+}
+
+fn loe_data_store_key(beacon_id: u32, round: u64) -> Vec<u8> {
+    let mut store_key = LOE_DATA_KEY.to_vec();
+    store_key.append(&mut beacon_id.to_be_bytes().to_vec());
+    store_key.append(&mut round.to_be_bytes().to_vec());
+    store_key
+}
+
+fn beacon_store_key(id: u32) -> Vec<u8> {
+    let mut store_key = BEACON_KEY.to_vec();
+    store_key.append(&mut id.to_be_bytes().to_vec());
+    store_key
+}
+
+fn contributor_store_key(address: AccAddress) -> Vec<u8> {
+    let mut store_key = CONTRIBUTOR_DATA_KEY.to_vec();
+    let addr: Vec<u8> = address.into();
+    store_key.append(&mut addr.to_vec());
+    store_key
+}
+
+fn fee_escrow_store_key(round: u64, scheme: u32, id: u32) -> Vec<u8> {
+    let mut store_key = FEE_ESCROW_KEY.to_vec();
+    store_key.append(&mut round.to_be_bytes().to_vec());
+    store_key.append(&mut scheme.to_be_bytes().to_vec());
+    store_key.append(&mut id.to_be_bytes().to_vec());
+    store_key
+}
+
+fn contributor_stats_store_key(address: AccAddress) -> Vec<u8> {
+    let mut store_key = PARTICIPATION_KEY.to_vec();
+    let addr: Vec<u8> = address.into();
+    store_key.append(&mut addr.to_vec());
+    store_key
+}
+
+fn owner_index_prefix(owner: AccAddress) -> Vec<u8> {
+    let mut store_key = OWNER_INDEX_KEY.to_vec();
+    let addr: Vec<u8> = owner.into();
+    store_key.append(&mut addr.to_vec());
+    store_key
+}
+
+// Keypairs requested by an address, ordered by round, scheme and id
+fn owner_index_store_key(owner: AccAddress, round: u64, scheme: u32, id: u32) -> Vec<u8> {
+    let mut store_key = owner_index_prefix(owner);
+    store_key.append(&mut round.to_be_bytes().to_vec());
+    store_key.append(&mut scheme.to_be_bytes().to_vec());
+    store_key.append(&mut id.to_be_bytes().to_vec());
+    store_key
+}
+
+fn released_queue_store_key(prune_height: u64, round: u64, scheme: u32, id: u32) -> Vec<u8> {
+    let mut store_key = RELEASED_QUEUE_KEY.to_vec();
+    store_key.append(&mut prune_height.to_be_bytes().to_vec());
+    store_key.append(&mut round.to_be_bytes().to_vec());
+    store_key.append(&mut scheme.to_be_bytes().to_vec());
+    store_key.append(&mut id.to_be_bytes().to_vec());
+    store_key
+}
+
+// Keypairs waiting for their public key, ordered by public key time
+fn pubkey_queue_store_key(pubkey_time: i64, round: u64, scheme: u32, id: u32) -> Vec<u8> {
+    let mut store_key = PUBKEY_QUEUE_KEY.to_vec();
+    store_key.append(&mut (pubkey_time.max(0) as u64).to_be_bytes().to_vec());
+    store_key.append(&mut round.to_be_bytes().to_vec());
+    store_key.append(&mut scheme.to_be_bytes().to_vec());
+    store_key.append(&mut id.to_be_bytes().to_vec());
+    store_key
+}
+
+// Keypairs waiting for the LOE data of their round, ordered by beacon and round
+fn loe_queue_store_key(beacon_id: u32, round: u64, scheme: u32, id: u32) -> Vec<u8> {
+    let mut store_key = LOE_QUEUE_KEY.to_vec();
+    store_key.append(&mut beacon_id.to_be_bytes().to_vec());
+    store_key.append(&mut round.to_be_bytes().to_vec());
+    store_key.append(&mut scheme.to_be_bytes().to_vec());
+    store_key.append(&mut id.to_be_bytes().to_vec());
+    store_key
+}
+
+fn archived_keypair_store_key(round: u64, scheme: u32, id: u32) -> Vec<u8> {
+    let mut store_key = ARCHIVED_KEYPAIR_KEY.to_vec();
+    store_key.append(&mut round.to_be_bytes().to_vec());
+    store_key.append(&mut scheme.to_be_bytes().to_vec());
+    store_key.append(&mut id.to_be_bytes().to_vec());
+    store_key
+}
+
+/// Page size used when a query doesn't set a limit
+const DEFAULT_PAGE_LIMIT: u64 = 100;
+/// Largest page size a query can ask for
+const MAX_PAGE_LIMIT: u64 = 1000;
+
+fn page_limit(page: &PageRequest) -> usize {
+    let limit = match page.limit {
+        0 => DEFAULT_PAGE_LIMIT,
+        limit => limit.min(MAX_PAGE_LIMIT),
+    };
+    limit as usize
+}
+
+/// Reads a page of rows from `start` up to, but not including, `end`. The rows are read with a
+/// bounded range iteration so only the requested page is loaded.
+fn paginate<DB: Database>(
+    store: &ImmutablePrefixStore<PrefixDB<DB>>,
+    start: Vec<u8>,
+    end: Option<Vec<u8>>,
+    pagination: Option<PageRequest>,
+) -> (Vec<(Vec<u8>, Vec<u8>)>, PageResponse) {
+    let page = pagination.unwrap_or_default();
+    let limit = page_limit(&page);
+    let start = if page.key > start { page.key } else { start };
+
+    // One extra row is read to find the start of the next page
+    let mut rows: Vec<(Vec<u8>, Vec<u8>)> = store
+        .range(start..)
+        .take_while(|(key, _)| end.as_ref().map_or(true, |end| key < end))
+        .skip(page.offset as usize)
+        .take(limit + 1)
+        .collect();
+
+    let next_key = if rows.len() > limit {
+        rows.pop().map(|(key, _)| key).unwrap_or_default()
+    } else {
+        vec![]
+    };
+
+    (rows, PageResponse { next_key })
+}
+
+/// Number of most recent windows the participation of a contributor is judged by. Contributors
+/// aren't jailed before that many windows were counted since they registered.
+const PARTICIPATION_WINDOWS: usize = 10;
+
+/// Error of a rejected contribution. The events of a failed tx are discarded, so its log names
+/// the keypair and the contributor like a `contribution_rejected` event would.
+fn contribution_rejected(msg: &MsgContribution, e: TimelockError) -> AppError {
+    AppError::Custom {
+        code: e.code(),
+        log: format!(
+            "contribution_rejected: round={} scheme={} id={} contributor={}: {}",
+            msg.round, msg.scheme, msg.id, msg.address, e
+        ),
+    }
+}
+
+/// Moves the keypair to `status` and records the change at `height`
+fn set_keypair_status(
+    keypair: &mut RawMsgKeyPair,
+    status: KeyPairStatus,
+    height: u64,
+) -> Result<(), TimelockError> {
+    if !keypair.status().can_transition_to(status) {
+        return Err(TimelockError::InvalidStatusTransition);
+    }
+
+    keypair.set_status(status);
+    keypair.status_history.push(RawKeyPairStatusChange {
+        status: status as i32,
+        height,
+    });
+
+    Ok(())
+}
+
+/// Fails a keypair whose stored status doesn't allow the change the begin blocker needs. Only an
+/// inconsistent genesis can store one, and it must not halt the chain.
+fn force_keypair_failed(keypair: &mut RawMsgKeyPair, height: u64) {
+    keypair.set_status(KeyPairStatus::Failed);
+    keypair.status_history.push(RawKeyPairStatusChange {
+        status: KeyPairStatus::Failed as i32,
+        height,
+    });
+}
+
+pub fn module_address() -> AccAddress {
+    AccAddress::from_bech32(TIMELOCK_MODULE_ADDRESS).expect("module address is valid")
+}
+
+// Heights are big endian so that the pending updates are iterated in height order
+fn pending_params_store_key(height: u64) -> Vec<u8> {
+    let mut store_key = PENDING_PARAMS_KEY.to_vec();
+    store_key.append(&mut height.to_be_bytes().to_vec());
+    store_key
 }
 
 #[derive(Debug, Clone)]
-pub struct Keeper<SK: StoreKey> {
+pub struct Keeper<SK: StoreKey, PSK: ParamsSubspaceKey> {
     store_key: SK,
+    params_keeper: TimelockParamsKeeper<SK, PSK>,
+    bank_keeper: bank::Keeper<SK, PSK>,
 }
 
-impl<SK: StoreKey> Keeper<SK> {
-    pub fn new(store_key: SK) -> Self {
-        Keeper { store_key }
+impl<SK: StoreKey, PSK: ParamsSubspaceKey> Keeper<SK, PSK> {
+    pub fn new(
+        store_key: SK,
+        params_keeper: ParamsKeeper<SK, PSK>,
+        params_store_key: SK,
+        params_subspace_key: PSK,
+        bank_keeper: bank::Keeper<SK, PSK>,
+    ) -> Self {
+        let params_keeper = TimelockParamsKeeper {
+            params_keeper,
+            params_subspace_key,
+            params_store_key,
+        };
+        Keeper {
+            store_key,
+            params_keeper,
+            bank_keeper,
+        }
+    }
+
+    pub fn get_params<T: Database>(&self, ctx: &mut TxContext<T, SK>) -> Params {
+        self.params_keeper.get(&ctx.as_any())
+    }
+
+    pub fn get_block_time<T: Database>(&self, ctx: &TxContext<T, SK>) -> i64 {
+        ctx.get_header().time.unix_timestamp()
+    }
+
+    pub fn query_params<T: Database>(&self, ctx: &QueryContext<T, SK>) -> Params {
+        self.params_keeper.query(ctx)
+    }
+
+    pub fn init_genesis<T: Database>(&self, ctx: &mut InitContext<T, SK>, genesis: GenesisState) {
+        if let Err(e) = genesis.validate() {
+            panic!("invalid timelock genesis state: {}", e);
+        }
+
+        let params = Params::try_from(genesis.params.expect("params are checked in validate"))
+            .expect("params are checked in validate");
+        self.params_keeper.set(&mut ctx.as_any(), params.clone());
+        let initial_height = ctx.as_any().get_height();
+
+        let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
+        tlcs_store.set(
+            STORE_VERSION_KEY.to_vec(),
+            STORE_VERSION.to_be_bytes().to_vec(),
+        );
+
+        let beacons = if genesis.beacons.is_empty() {
+            default_beacons()
+        } else {
+            genesis.beacons
+        };
+        for beacon in beacons {
+            tlcs_store.set(beacon_store_key(beacon.id), beacon.encode_to_vec());
+        }
+
+        for keypair in genesis.keypairs {
+            // Keypairs which are released, failed or cancelled don't have any pending work
+            let status = keypair.status();
+            if status == KeyPairStatus::Requested || status == KeyPairStatus::Collecting {
+                tlcs_store.set(
+                    pubkey_queue_store_key(
+                        keypair.pubkey_time,
+                        keypair.round,
+                        keypair.scheme,
+                        keypair.id,
+                    ),
+                    vec![1],
+                );
+            } else if status == KeyPairStatus::PublicKeyReady
+                || status == KeyPairStatus::AwaitingBeacon
+            {
+                tlcs_store.set(
+                    loe_queue_store_key(
+                        keypair.beacon_id,
+                        keypair.round,
+                        keypair.scheme,
+                        keypair.id,
+                    ),
+                    vec![1],
+                );
+            }
+
+            // The release height isn't known anymore, so the retention period starts at the
+            // initial height of the chain
+            if status.is_final() && params.retention_blocks > 0 {
+                tlcs_store.set(
+                    released_queue_store_key(
+                        initial_height + params.retention_blocks,
+                        keypair.round,
+                        keypair.scheme,
+                        keypair.id,
+                    ),
+                    vec![1],
+                );
+            }
+
+            if !keypair.owner.is_empty() {
+                let owner = AccAddress::from_bech32(&keypair.owner)
+                    .expect("invalid keypair owner address in genesis");
+                tlcs_store.set(
+                    owner_index_store_key(owner, keypair.round, keypair.scheme, keypair.id),
+                    vec![1],
+                );
+            }
+
+            tlcs_store.set(
+                keypair_store_key(keypair.round, keypair.scheme, keypair.id),
+                keypair.encode_to_vec(),
+            );
+        }
+
+        for keypair in genesis.archived_keypairs {
+            tlcs_store.set(
+                archived_keypair_store_key(keypair.round, keypair.scheme, keypair.id),
+                keypair.encode_to_vec(),
+            );
+        }
+
+        for contribution in genesis.contributions {
+            let address = AccAddress::from_bech32(&contribution.address)
+                .expect("invalid contribution address in genesis");
+            tlcs_store.set(
+                contribution_store_key(
+                    contribution.round,
+                    contribution.scheme,
+                    contribution.id,
+                    address,
+                ),
+                contribution.encode_to_vec(),
+            );
+        }
+
+        for loe_data in genesis.loe_data {
+            tlcs_store.set(
+                loe_data_store_key(loe_data.beacon_id, loe_data.round),
+                loe_data.encode_to_vec(),
+            );
+        }
+
+        if !genesis.authority.is_empty() {
+            tlcs_store.set(AUTHORITY_KEY.to_vec(), genesis.authority.into_bytes());
+        }
+
+        // The module account balance covering the bonds and the escrowed fees is checked with
+        // the bank genesis, see `GenesisState::module_holdings`
+        for contributor in genesis.contributors {
+            let address = AccAddress::from_bech32(&contributor.address)
+                .expect("invalid contributor address in genesis");
+            tlcs_store.set(contributor_store_key(address), contributor.encode_to_vec());
+        }
+
+        for stats in genesis.contributor_stats {
+            let address = AccAddress::from_bech32(&stats.address)
+                .expect("invalid contributor stats address in genesis");
+            tlcs_store.set(contributor_stats_store_key(address), stats.encode_to_vec());
+        }
+
+        for escrow in genesis.fee_escrows {
+            tlcs_store.set(
+                fee_escrow_store_key(escrow.round, escrow.scheme, escrow.id),
+                escrow.encode_to_vec(),
+            );
+        }
+    }
+
+    pub fn export_genesis<T: Database>(&self, ctx: &QueryContext<T, SK>) -> GenesisState {
+        let authority = match ctx.get_kv_store(&self.store_key).get(&AUTHORITY_KEY) {
+            Some(authority) => String::from_utf8(authority)
+                .expect("invalid data in database - possible database corruption"),
+            None => "".to_string(),
+        };
+
+        GenesisState {
+            keypairs: self.export_rows(ctx, &KEYPAIR_DATA_KEY),
+            contributions: self.export_rows(ctx, &PARTICIPANT_DATA_KEY),
+            loe_data: self.export_rows(ctx, &LOE_DATA_KEY),
+            params: Some(self.query_params(ctx).into()),
+            authority,
+            contributors: self.export_rows(ctx, &CONTRIBUTOR_DATA_KEY),
+            fee_escrows: self.query_fee_escrows(ctx),
+            archived_keypairs: self.export_rows(ctx, &ARCHIVED_KEYPAIR_KEY),
+            beacons: self.export_rows(ctx, &BEACON_KEY),
+            contributor_stats: self.export_rows(ctx, &PARTICIPATION_KEY),
+        }
+    }
+
+    // The genesis export needs every row, unlike the paginated queries
+    fn export_rows<T: Database, M: Message + Default>(
+        &self,
+        ctx: &QueryContext<T, SK>,
+        prefix: &[u8],
+    ) -> Vec<M> {
+        ctx.get_kv_store(&self.store_key)
+            .get_immutable_prefix_store(prefix.to_vec())
+            .range(..)
+            .map(|(_, row)| {
+                M::decode::<Bytes>(row.into())
+                    .expect("invalid data in database - possible database corruption")
+            })
+            .collect()
+    }
+
+    pub fn update_params<T: Database>(
+        &self,
+        ctx: &mut TxContext<T, SK>,
+        msg: &MsgUpdateParams,
+    ) -> Result<(), AppError> {
+        let tlcs_store = ctx.get_kv_store(&self.store_key);
+        if tlcs_store.get(&AUTHORITY_KEY) != Some(msg.authority.to_string().into_bytes()) {
+            return Err(AppError::InvalidRequest(
+                "Only the timelock authority can update the params.".into(),
+            ));
+        }
+
+        if msg.height <= ctx.get_height() {
+            return Err(AppError::InvalidRequest(
+                "The params update height must be in the future.".into(),
+            ));
+        }
+
+        let mut params = self.get_params(ctx);
+        params.update(msg);
+        params.validate().map_err(AppError::InvalidRequest)?;
+
+        let store_key = pending_params_store_key(msg.height);
+        let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
+        if tlcs_store.get(&store_key).is_some() {
+            return Err(AppError::InvalidRequest(format!(
+                "A params update is already scheduled for height {}",
+                msg.height
+            )));
+        }
+
+        info!("UPDATE PARAMS TX: scheduled for height: {:?}", msg.height);
+        tlcs_store.set(
+            store_key,
+            <MsgUpdateParams as Into<RawMsgUpdateParams>>::into(msg.to_owned()).encode_to_vec(),
+        );
+
+        ctx.push_event(
+            TimelockEvent::ParamsUpdateScheduled {
+                authority: msg.authority.clone(),
+                height: msg.height,
+            }
+            .into(),
+        );
+
+        Ok(())
+    }
+
+    pub fn update_beacon<T: Database>(
+        &self,
+        ctx: &mut TxContext<T, SK>,
+        msg: &MsgUpdateBeacon,
+    ) -> Result<(), AppError> {
+        let tlcs_store = ctx.get_kv_store(&self.store_key);
+        if tlcs_store.get(&AUTHORITY_KEY) != Some(msg.authority.to_string().into_bytes()) {
+            return Err(AppError::InvalidRequest(
+                "Only the timelock authority can update the beacons.".into(),
+            ));
+        }
+
+        validate_beacon(&msg.beacon).map_err(AppError::InvalidRequest)?;
+
+        // The keypairs of a registered beacon were made for its public key and rounds
+        if let Some(registered) = self.get_beacon(ctx, msg.beacon.id) {
+            let unchanged = RawBeacon {
+                retired: msg.beacon.retired,
+                url: msg.beacon.url.clone(),
+                ..registered
+            };
+            if unchanged != msg.beacon {
+                return Err(AppError::InvalidRequest(
+                    "Only the retired flag and the url of a registered beacon can change.".into(),
+                ));
+            }
+        }
+
+        info!(
+            "UPDATE BEACON TX: Id: {:?}, Retired: {:?}",
+            msg.beacon.id, msg.beacon.retired
+        );
+        let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
+        tlcs_store.set(beacon_store_key(msg.beacon.id), msg.beacon.encode_to_vec());
+
+        ctx.push_event(
+            TimelockEvent::BeaconUpdated {
+                id: msg.beacon.id,
+                chain_hash: msg.beacon.chain_hash.clone(),
+                retired: msg.beacon.retired,
+            }
+            .into(),
+        );
+
+        Ok(())
+    }
+
+    pub fn get_beacon<T: Database>(&self, ctx: &TxContext<T, SK>, id: u32) -> Option<RawBeacon> {
+        ctx.get_kv_store(&self.store_key)
+            .get(&beacon_store_key(id))
+            .map(|raw| {
+                RawBeacon::decode::<Bytes>(raw.into())
+                    .expect("invalid data in database - possible database corruption")
+            })
+    }
+
+    fn get_beacons<T: Database>(&self, ctx: &TxContext<T, SK>) -> Vec<RawBeacon> {
+        ctx.get_kv_store(&self.store_key)
+            .get_immutable_prefix_store(BEACON_KEY.to_vec())
+            .range(..)
+            .map(|(_, raw)| {
+                RawBeacon::decode::<Bytes>(raw.into())
+                    .expect("invalid data in database - possible database corruption")
+            })
+            .collect()
+    }
+
+    // Keypairs only record registered beacons, so a missing one means the store is corrupt
+    fn keypair_beacon<T: Database>(
+        &self,
+        ctx: &TxContext<T, SK>,
+        keypair: &RawMsgKeyPair,
+    ) -> Box<dyn Beacon> {
+        self.get_beacon(ctx, keypair.beacon_id)
+            .as_ref()
+            .and_then(registered_beacon)
+            .expect("invalid data in database - possible database corruption")
+    }
+
+    /// Checks that new keypairs can be requested for the beacon
+    fn check_beacon_selectable<T: Database>(
+        &self,
+        ctx: &TxContext<T, SK>,
+        beacon_id: u32,
+    ) -> Result<(), AppError> {
+        match self.get_beacon(ctx, beacon_id) {
+            Some(beacon) if beacon.retired => Err(TimelockError::BeaconRetired.into()),
+            Some(beacon)
+                if !registered_beacon(&beacon).map_or(false, |b| b.supports_timelock()) =>
+            {
+                Err(TimelockError::BeaconNotTimelockable.into())
+            }
+            Some(_) => Ok(()),
+            None => Err(TimelockError::BeaconNotFound.into()),
+        }
+    }
+
+    pub fn query_beacons<T: Database>(
+        &self,
+        ctx: &QueryContext<T, SK>,
+        pagination: Option<PageRequest>,
+    ) -> QueryBeaconsResponse {
+        let tlcs_store = ctx.get_kv_store(&self.store_key);
+        let prefix_store = tlcs_store.get_immutable_prefix_store(BEACON_KEY.to_vec());
+        let (rows, page) = paginate(&prefix_store, vec![], None, pagination);
+
+        let mut beacons = vec![];
+        for (_, raw) in rows {
+            let beacon: RawBeacon = RawBeacon::decode::<Bytes>(raw.into())
+                .expect("invalid data in database - possible database corruption");
+            beacons.push(beacon);
+        }
+
+        QueryBeaconsResponse {
+            beacons,
+            pagination: Some(page),
+        }
+    }
+
+    /// Converts a store written by an older version of the module to the current layout. Runs
+    /// once, in the first block after the upgrade.
+    pub fn migrate_store<T: Database>(&self, ctx: &mut TxContext<T, SK>) {
+        let version = match ctx.get_kv_store(&self.store_key).get(&STORE_VERSION_KEY) {
+            Some(raw) => u64::from_be_bytes(
+                raw.try_into()
+                    .expect("invalid data in database - possible database corruption"),
+            ),
+            None => 0,
+        };
+
+        if version >= STORE_VERSION {
+            return;
+        }
+
+        info!(
+            "MIGRATE: converting timelock store from version {:?} to {:?}",
+            version, STORE_VERSION
+        );
+
+        // The first release had no params, they must be set before anything reads them
+        if !self.params_keeper.is_set(&ctx.as_any()) {
+            self.params_keeper.set(&mut ctx.as_any(), Params::default());
+        }
+
+        self.migrate_first_release_store(ctx);
+
+        let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
+        tlcs_store.set(
+            STORE_VERSION_KEY.to_vec(),
+            STORE_VERSION.to_be_bytes().to_vec(),
+        );
+    }
+
+    /// Removes all the rows under a prefix and returns their values in key order
+    fn take_rows<T: Database>(&self, ctx: &mut TxContext<T, SK>, prefix: &[u8]) -> Vec<Vec<u8>> {
+        let rows: Vec<(Vec<u8>, Vec<u8>)> = ctx
+            .get_kv_store(&self.store_key)
+            .get_immutable_prefix_store(prefix.to_vec())
+            .range(..)
+            .collect();
+
+        let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
+        rows.into_iter()
+            .map(|(key, row)| {
+                let mut store_key = prefix.to_vec();
+                store_key.append(&mut key.clone());
+                tlcs_store.delete(&store_key);
+                row
+            })
+            .collect()
+    }
+
+    /// The first release keyed the keypairs by round|scheme|id, but multi requests by
+    /// round|scheme only, the contributions by round|scheme|address and the LOE data by round,
+    /// all little endian. Those keys can't be converted in place, so every row is decoded and
+    /// written again under the key built from its values.
+    fn migrate_first_release_store<T: Database>(&self, ctx: &mut TxContext<T, SK>) {
+        let height = ctx.get_height();
+        let params = self.get_params(ctx);
+
+        // All the old rows are removed first so that a new key can't be overwritten
+        let contribution_rows = self.take_rows(ctx, &PARTICIPANT_DATA_KEY);
+        let keypair_rows = self.take_rows(ctx, &KEYPAIR_DATA_KEY);
+        let loe_rows = self.take_rows(ctx, &LOE_DATA_KEY);
+
+        let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
+        tlcs_store.delete(&LEGACY_CONTRIBUTION_THRESHOLD_KEY);
+
+        for beacon in default_beacons() {
+            tlcs_store.set(beacon_store_key(beacon.id), beacon.encode_to_vec());
+        }
+
+        // The stored LOE data was all taken from the quicknet beacon
+        for row in loe_rows {
+            let mut loe_data = RawMsgLoeData::decode::<Bytes>(row.into())
+                .expect("invalid data in database - possible database corruption");
+            loe_data.beacon_id = QUICKNET_BEACON_ID;
+            tlcs_store.set(
+                loe_data_store_key(QUICKNET_BEACON_ID, loe_data.round),
+                loe_data.encode_to_vec(),
+            );
+        }
+
+        // The same id could be stored more than once. The first keypair in key order keeps it,
+        // the others get the next free id of their round and scheme.
+        let mut keypairs: BTreeMap<(u64, u32, u32), RawMsgKeyPair> = BTreeMap::new();
+        for row in keypair_rows {
+            let mut keypair = RawMsgKeyPair::decode::<Bytes>(row.into())
+                .expect("invalid data in database - possible database corruption");
+            let (round, scheme) = (keypair.round, keypair.scheme);
+            if keypairs.contains_key(&(round, scheme, keypair.id)) {
+                let last_id = keypairs
+                    .range((round, scheme, 0)..=(round, scheme, u32::MAX))
+                    .next_back()
+                    .map(|((_, _, id), _)| *id)
+                    .unwrap_or_default();
+                info!(
+                    "MIGRATE: keypair {:?} {:?} {:?} is stored twice, moving it to id {:?}",
+                    round,
+                    scheme,
+                    keypair.id,
+                    last_id + 1
+                );
+                keypair.id = last_id + 1;
+            }
+            keypairs.insert((round, scheme, keypair.id), keypair);
+        }
+
+        // The first release made the keys of a keypair from all the shares of its round and
+        // scheme, whatever id the share named. Every keypair of the round and scheme gets a copy
+        // so its secret key is made from the same shares as its public key.
+        let mut contributions: BTreeMap<(u64, u32), Vec<RawMsgContribution>> = BTreeMap::new();
+        for row in contribution_rows {
+            let contribution = RawMsgContribution::decode::<Bytes>(row.into())
+                .expect("invalid data in database - possible database corruption");
+            contributions
+                .entry((contribution.round, contribution.scheme))
+                .or_default()
+                .push(contribution);
+        }
+
+        for ((round, scheme), shares) in &contributions {
+            let ids: Vec<u32> = keypairs
+                .range((*round, *scheme, 0)..=(*round, *scheme, u32::MAX))
+                .map(|((_, _, id), _)| *id)
+                .collect();
+            if ids.is_empty() {
+                info!(
+                    "MIGRATE: dropping {:?} contributions for round {:?} scheme {:?} without keypairs",
+                    shares.len(),
+                    round,
+                    scheme
+                );
+            }
+
+            for id in ids {
+                for share in shares {
+                    let address = AccAddress::from_bech32(&share.address)
+                        .expect("invalid data in database - possible database corruption");
+                    let contribution = RawMsgContribution {
+                        id,
+                        ..share.clone()
+                    };
+                    tlcs_store.set(
+                        contribution_store_key(*round, *scheme, id, address),
+                        contribution.encode_to_vec(),
+                    );
+                }
+            }
+        }
+
+        // The heights of the earlier status changes aren't known, so the history starts with
+        // the status at the migration. All keypairs used the quicknet beacon.
+        for ((round, scheme, id), mut keypair) in keypairs {
+            let status = if !keypair.private_key.is_empty() {
+                KeyPairStatus::Released
+            } else if !keypair.public_key.is_empty() {
+                KeyPairStatus::PublicKeyReady
+            } else if contributions.contains_key(&(round, scheme)) {
+                KeyPairStatus::Collecting
+            } else {
+                KeyPairStatus::Requested
+            };
+
+            keypair.set_status(status);
+            keypair.status_history = vec![RawKeyPairStatusChange {
+                status: status as i32,
+                height,
+            }];
+            keypair.beacon_id = QUICKNET_BEACON_ID;
+
+            match status {
+                KeyPairStatus::Requested | KeyPairStatus::Collecting => tlcs_store.set(
+                    pubkey_queue_store_key(keypair.pubkey_time, round, scheme, id),
+                    vec![1],
+                ),
+                KeyPairStatus::PublicKeyReady => tlcs_store.set(
+                    loe_queue_store_key(QUICKNET_BEACON_ID, round, scheme, id),
+                    vec![1],
+                ),
+                status if status.is_final() && params.retention_blocks > 0 => tlcs_store.set(
+                    released_queue_store_key(height + params.retention_blocks, round, scheme, id),
+                    vec![1],
+                ),
+                _ => {}
+            }
+
+            tlcs_store.set(
+                keypair_store_key(round, scheme, id),
+                keypair.encode_to_vec(),
+            );
+        }
+    }
+
+    /// Applies the params updates which were scheduled for this block or earlier
+    pub fn apply_params_updates<T: Database>(&self, ctx: &mut TxContext<T, SK>) {
+        let height = ctx.get_height();
+        let tlcs_store = ctx.get_kv_store(&self.store_key);
+        let updates: Vec<(Vec<u8>, Vec<u8>)> = tlcs_store
+            .get_immutable_prefix_store(PENDING_PARAMS_KEY.to_vec())
+            .range(..(height + 1).to_be_bytes().to_vec())
+            .collect();
+
+        for (_, row) in updates {
+            let update: MsgUpdateParams = RawMsgUpdateParams::decode::<Bytes>(row.into())
+                .expect("invalid data in database - possible database corruption")
+                .try_into()
+                .expect("invalid data in database - possible database corruption");
+
+            // The update was checked when it was scheduled, but an earlier update could have
+            // changed the params since then
+            let mut params = self.get_params(ctx);
+            params.update(&update);
+            match params.validate() {
+                Ok(_) => {
+                    info!(
+                        "BEGINBLOCKER: applying params update from: {:?}",
+                        update.height
+                    );
+                    self.params_keeper.set(&mut ctx.as_any(), params);
+                    ctx.push_event(
+                        TimelockEvent::ParamsUpdated {
+                            height: update.height,
+                        }
+                        .into(),
+                    );
+                }
+                Err(e) => info!("BEGINBLOCKER: invalid params update skipped: {:?}", e),
+            }
+
+            let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
+            tlcs_store.delete(&pending_params_store_key(update.height));
+        }
+    }
+
+    fn send_coins<T: Database>(
+        &self,
+        ctx: &mut TxContext<T, SK>,
+        from_address: AccAddress,
+        to_address: AccAddress,
+        denom: &str,
+        amount: u64,
+    ) -> Result<(), AppError> {
+        let denom = Denom::try_from(denom.to_owned())
+            .map_err(|e| AppError::InvalidRequest(format!("invalid denom: {}", e)))?;
+        let amount = SendCoins::new(vec![Coin {
+            denom,
+            amount: amount.into(),
+        }])
+        .map_err(|e| AppError::InvalidRequest(e.to_string()))?;
+
+        self.bank_keeper.send_coins_from_account_to_account(
+            &mut ctx.as_any(),
+            &MsgSend {
+                from_address,
+                to_address,
+                amount,
+            },
+        )
+    }
+
+    pub fn register_contributor<T: Database>(
+        &self,
+        ctx: &mut TxContext<T, SK>,
+        msg: &MsgRegisterContributor,
+    ) -> Result<(), AppError> {
+        let store_key = contributor_store_key(msg.address.clone());
+        if ctx.get_kv_store(&self.store_key).get(&store_key).is_some() {
+            return Err(AppError::InvalidRequest(
+                "The address is already a registered contributor.".into(),
+            ));
+        }
+
+        let params = self.get_params(ctx);
+        if params.contributor_bond > 0 {
+            self.send_coins(
+                ctx,
+                msg.address.clone(),
+                module_address(),
+                &params.bond_denom,
+                params.contributor_bond,
+            )?;
+        }
+
+        let contributor = RawContributor {
+            address: msg.address.to_string(),
+            bond: params.contributor_bond,
+            bond_denom: params.bond_denom,
+            registered_height: ctx.get_height(),
+            registered_time: self.get_block_time(ctx),
+            jailed: false,
+        };
+
+        // Windows before this registration don't count against it, so a contributor which was
+        // jailed starts over when it bonds again
+        let stats_key = contributor_stats_store_key(msg.address.clone());
+        if ctx.get_kv_store(&self.store_key).get(&stats_key).is_some() {
+            let mut stats = self.get_contributor_stats(ctx, msg.address.clone());
+            stats.recent_windows.clear();
+            ctx.get_mutable_kv_store(&self.store_key)
+                .set(stats_key, stats.encode_to_vec());
+        }
+
+        info!("REGISTER CONTRIBUTOR TX: {:?}", contributor.address);
+        let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
+        tlcs_store.set(store_key, contributor.encode_to_vec());
+
+        ctx.push_event(
+            TimelockEvent::ContributorRegistered {
+                address: msg.address.clone(),
+            }
+            .into(),
+        );
+
+        Ok(())
+    }
+
+    pub fn deregister_contributor<T: Database>(
+        &self,
+        ctx: &mut TxContext<T, SK>,
+        msg: &MsgDeregisterContributor,
+    ) -> Result<(), AppError> {
+        let store_key = contributor_store_key(msg.address.clone());
+        let contributor = match ctx.get_kv_store(&self.store_key).get(&store_key) {
+            Some(raw) => RawContributor::decode::<Bytes>(raw.into())
+                .expect("invalid data in database - possible database corruption"),
+            None => {
+                return Err(AppError::InvalidRequest(
+                    "The address is not a registered contributor.".into(),
+                ))
+            }
+        };
+
+        // The bond which was paid at registration is refunded, even if the params changed since.
+        // A jailed contributor forfeits it, so leaving and registering again isn't free.
+        if contributor.bond > 0 && !contributor.jailed {
+            self.send_coins(
+                ctx,
+                module_address(),
+                msg.address.clone(),
+                &contributor.bond_denom,
+                contributor.bond,
+            )?;
+        }
+
+        // The participation stats are kept for when the address registers again
+        info!("DEREGISTER CONTRIBUTOR TX: {:?}", contributor.address);
+        let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
+        tlcs_store.delete(&store_key);
+
+        ctx.push_event(
+            TimelockEvent::ContributorDeregistered {
+                address: msg.address.clone(),
+            }
+            .into(),
+        );
+
+        Ok(())
+    }
+
+    fn get_contributors<T: Database>(&self, ctx: &TxContext<T, SK>) -> Vec<RawContributor> {
+        ctx.get_kv_store(&self.store_key)
+            .get_immutable_prefix_store(CONTRIBUTOR_DATA_KEY.to_vec())
+            .range(..)
+            .map(|(_, raw)| {
+                RawContributor::decode::<Bytes>(raw.into())
+                    .expect("invalid data in database - possible database corruption")
+            })
+            .collect()
+    }
+
+    /// Registered contributors which aren't jailed
+    pub fn is_registered_contributor<T: Database>(
+        &self,
+        ctx: &TxContext<T, SK>,
+        address: AccAddress,
+    ) -> bool {
+        match ctx
+            .get_kv_store(&self.store_key)
+            .get(&contributor_store_key(address))
+        {
+            Some(raw) => {
+                !RawContributor::decode::<Bytes>(raw.into())
+                    .expect("invalid data in database - possible database corruption")
+                    .jailed
+            }
+            None => false,
+        }
+    }
+
+    pub fn active_contributor_count<T: Database>(&self, ctx: &TxContext<T, SK>) -> u32 {
+        self.get_contributors(ctx)
+            .iter()
+            .filter(|contributor| !contributor.jailed)
+            .count() as u32
+    }
+
+    fn get_contributor_stats<T: Database>(
+        &self,
+        ctx: &TxContext<T, SK>,
+        address: AccAddress,
+    ) -> RawContributorStats {
+        match ctx
+            .get_kv_store(&self.store_key)
+            .get(&contributor_stats_store_key(address.clone()))
+        {
+            Some(raw) => RawContributorStats::decode::<Bytes>(raw.into())
+                .expect("invalid data in database - possible database corruption"),
+            None => RawContributorStats {
+                address: address.to_string(),
+                contributed_count: 0,
+                missed_count: 0,
+                recent_windows: vec![],
+            },
+        }
+    }
+
+    /// Counts for every registered contributor whether they contributed to the keypair, once
+    /// its contribution window closed, and jails contributors with a too low participation in
+    /// their recent windows
+    fn record_participation<T: Database>(
+        &self,
+        ctx: &mut TxContext<T, SK>,
+        keypair: &RawMsgKeyPair,
+        contributed: &[String],
+    ) {
+        let params = self.get_params(ctx);
+
+        for mut contributor in self.get_contributors(ctx) {
+            if contributor.jailed || contributor.registered_time > keypair.contribution_start {
+                continue;
+            }
+
+            let address = AccAddress::from_bech32(&contributor.address)
+                .expect("invalid data in database - possible database corruption");
+            let mut stats = self.get_contributor_stats(ctx, address.clone());
+            let did_contribute = contributed.contains(&contributor.address);
+            if did_contribute {
+                stats.contributed_count += 1;
+            } else {
+                stats.missed_count += 1;
+            }
+
+            stats.recent_windows.push(did_contribute);
+            let windows = stats.recent_windows.len();
+            if windows > PARTICIPATION_WINDOWS {
+                stats
+                    .recent_windows
+                    .drain(..windows - PARTICIPATION_WINDOWS);
+            }
+
+            let recent_contributed = stats
+                .recent_windows
+                .iter()
+                .filter(|contributed| **contributed)
+                .count() as u64;
+            if params.min_participation_ratio > 0
+                && stats.recent_windows.len() == PARTICIPATION_WINDOWS
+                && recent_contributed * 1000
+                    < PARTICIPATION_WINDOWS as u64 * params.min_participation_ratio as u64
+            {
+                info!("CONTRIBUTOR JAILED: {:?}", contributor.address);
+                contributor.jailed = true;
+                ctx.push_event(
+                    TimelockEvent::ContributorJailed {
+                        address: contributor.address.clone(),
+                    }
+                    .into(),
+                );
+            }
+
+            let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
+            tlcs_store.set(
+                contributor_stats_store_key(address.clone()),
+                stats.encode_to_vec(),
+            );
+            tlcs_store.set(contributor_store_key(address), contributor.encode_to_vec());
+        }
+    }
+
+    pub fn query_contributor_stats<T: Database>(
+        &self,
+        ctx: &QueryContext<T, SK>,
+        address: AccAddress,
+    ) -> QueryContributorStatsResponse {
+        let tlcs_store = ctx.get_kv_store(&self.store_key);
+
+        let contributor = tlcs_store
+            .get(&contributor_store_key(address.clone()))
+            .map(|raw| {
+                RawContributor::decode::<Bytes>(raw.into())
+                    .expect("invalid data in database - possible database corruption")
+            });
+
+        let stats = tlcs_store
+            .get(&contributor_stats_store_key(address))
+            .map(|raw| {
+                RawContributorStats::decode::<Bytes>(raw.into())
+                    .expect("invalid data in database - possible database corruption")
+            });
+
+        let uptime = match &stats {
+            Some(stats) if stats.contributed_count + stats.missed_count > 0 => {
+                (stats.contributed_count * 1000 / (stats.contributed_count + stats.missed_count))
+                    as u32
+            }
+            _ => 0,
+        };
+
+        QueryContributorStatsResponse {
+            contributor,
+            uptime,
+            stats,
+        }
+    }
+
+    /// Number of contributions needed to make a public key in this block. It never exceeds the
+    /// active contributors, a higher threshold couldn't be reached.
+    pub fn contribution_threshold<T: Database>(&self, ctx: &mut TxContext<T, SK>) -> u32 {
+        let params = self.get_params(ctx);
+        let active = self.active_contributor_count(ctx);
+        let threshold = (active as u64 * params.threshold_numerator as u64
+            / params.threshold_denominator as u64) as u32
+            + 1;
+
+        threshold.max(params.min_contribution_threshold)
     }
 
-    pub fn open_process_count<T: Database>(
+    pub fn query_contributors<T: Database>(
+        &self,
+        ctx: &QueryContext<T, SK>,
+        pagination: Option<PageRequest>,
+    ) -> QueryContributorsResponse {
+        let tlcs_store = ctx.get_kv_store(&self.store_key);
+        let prefix_store = tlcs_store.get_immutable_prefix_store(CONTRIBUTOR_DATA_KEY.to_vec());
+
+        let (rows, page) = paginate(&prefix_store, vec![], None, pagination);
+
+        let mut contributors = vec![];
+        for (_, raw) in rows {
+            let contributor: RawContributor = RawContributor::decode::<Bytes>(raw.into())
+                .expect("invalid data in database - possible database corruption");
+            contributors.push(contributor);
+        }
+
+        QueryContributorsResponse {
+            contributors,
+            pagination: Some(page),
+        }
+    }
+
+    /// Returns the id of a new keypair for the round and scheme. Ids are never reused, the ids
+    /// of archived keypairs neither, since escrows, queue entries and owner index rows may still
+    /// refer to them.
+    pub fn next_keypair_id<T: Database>(
         &self,
         ctx: &mut TxContext<T, SK>,
         round: u64,
@@ -98,15 +1276,208 @@ impl<SK: StoreKey> Keeper<SK> {
     ) -> u32 {
         let tlcs_store = ctx.get_kv_store(&self.store_key);
 
-        // Make store search key
-        let mut store_key = KEYPAIR_DATA_KEY.to_vec();
-        store_key.append(&mut round.to_le_bytes().to_vec());
-        store_key.append(&mut scheme.to_le_bytes().to_vec());
+        // The ids are big endian so the last key of the round and scheme holds the highest id
+        let last_id = |prefix: &[u8]| -> u32 {
+            let mut store_key = prefix.to_vec();
+            store_key.append(&mut round.to_be_bytes().to_vec());
+            store_key.append(&mut scheme.to_be_bytes().to_vec());
+
+            tlcs_store
+                .get_immutable_prefix_store(store_key)
+                .range(..)
+                .last()
+                .map(|(key, _)| {
+                    u32::from_be_bytes(
+                        key.as_slice()
+                            .try_into()
+                            .expect("invalid key in database - possible database corruption"),
+                    )
+                })
+                .unwrap_or(0)
+        };
+
+        last_id(&KEYPAIR_DATA_KEY).max(last_id(&ARCHIVED_KEYPAIR_KEY)) + 1
+    }
 
-        let prefix_store = tlcs_store.get_immutable_prefix_store(store_key);
-        let the_keys = prefix_store.range(..);
+    /// Stores a new keypair request, escrows its fee and returns its id
+    fn create_keypair<T: Database>(
+        &self,
+        ctx: &mut TxContext<T, SK>,
+        payer: AccAddress,
+        round: u64,
+        scheme: u32,
+        pubkey_time: i64,
+        canonical: bool,
+        beacon_id: u32,
+    ) -> Result<u32, AppError> {
+        let params = self.get_params(ctx);
+        let keycount = self.next_keypair_id(ctx, round, scheme);
+        let block_time = self.get_block_time(ctx);
+
+        if params.request_fee > 0 {
+            self.send_coins(
+                ctx,
+                payer.clone(),
+                module_address(),
+                &params.bond_denom,
+                params.request_fee,
+            )?;
+
+            let escrow = RawFeeEscrow {
+                round,
+                scheme,
+                id: keycount,
+                payer: payer.to_string(),
+                amount: params.request_fee,
+                denom: params.bond_denom,
+            };
+            let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
+            tlcs_store.set(
+                fee_escrow_store_key(round, scheme, keycount),
+                escrow.encode_to_vec(),
+            );
+        }
+
+        let key_data: RawMsgKeyPair = RawMsgKeyPair {
+            round,
+            scheme,
+            id: keycount,
+            pubkey_time,
+            public_key: "".to_string(),
+            private_key: "".to_string(),
+            contribution_threshold: 0,
+            contribution_start: block_time,
+            canonical,
+            status: KeyPairStatus::Requested as i32,
+            status_history: vec![RawKeyPairStatusChange {
+                status: KeyPairStatus::Requested as i32,
+                height: ctx.get_height(),
+            }],
+            owner: payer.to_string(),
+            beacon_id,
+        };
+
+        let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
+        tlcs_store.set(
+            pubkey_queue_store_key(pubkey_time, round, scheme, keycount),
+            vec![1],
+        );
+        tlcs_store.set(
+            owner_index_store_key(payer, round, scheme, keycount),
+            vec![1],
+        );
+        tlcs_store.set(
+            keypair_store_key(round, scheme, keycount),
+            key_data.encode_to_vec(),
+        );
+
+        ctx.push_event(TimelockEvent::KeyRequestOpened((&key_data).into()).into());
+
+        Ok(keycount)
+    }
+
+    fn get_fee_escrow<T: Database>(
+        &self,
+        ctx: &TxContext<T, SK>,
+        round: u64,
+        scheme: u32,
+        id: u32,
+    ) -> Option<RawFeeEscrow> {
+        ctx.get_kv_store(&self.store_key)
+            .get(&fee_escrow_store_key(round, scheme, id))
+            .map(|raw| {
+                RawFeeEscrow::decode::<Bytes>(raw.into())
+                    .expect("invalid data in database - possible database corruption")
+            })
+    }
+
+    /// Splits the escrowed fee of a keypair between the given addresses. The addresses are
+    /// sorted so every node makes the same split, the remainder goes to the first address. The
+    /// escrow is removed once it is fully paid, a payment which fails stays escrowed and the
+    /// first error is returned.
+    fn release_fee_escrow<T: Database>(
+        &self,
+        ctx: &mut TxContext<T, SK>,
+        mut escrow: RawFeeEscrow,
+        mut recipients: Vec<String>,
+    ) -> Result<(), AppError> {
+        recipients.sort();
+        recipients.dedup();
+
+        let mut failure: Option<AppError> = None;
+
+        if !recipients.is_empty() {
+            let share = escrow.amount / recipients.len() as u64;
+            let remainder = escrow.amount % recipients.len() as u64;
+
+            for (i, recipient) in recipients.iter().enumerate() {
+                let amount = if i == 0 { share + remainder } else { share };
+                if amount == 0 {
+                    continue;
+                }
+
+                let address = AccAddress::from_bech32(recipient)
+                    .expect("invalid data in database - possible database corruption");
+                match self.send_coins(ctx, module_address(), address, &escrow.denom, amount) {
+                    Ok(()) => escrow.amount -= amount,
+                    Err(e) => {
+                        failure.get_or_insert(e);
+                    }
+                }
+            }
+        }
+
+        let store_key = fee_escrow_store_key(escrow.round, escrow.scheme, escrow.id);
+        let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
+        if escrow.amount == 0 {
+            tlcs_store.delete(&store_key);
+        } else {
+            tlcs_store.set(store_key, escrow.encode_to_vec());
+        }
 
-        (the_keys.count() + 1) as u32 // from usize
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    pub fn query_fee_escrows<T: Database>(&self, ctx: &QueryContext<T, SK>) -> Vec<RawFeeEscrow> {
+        let tlcs_store = ctx.get_kv_store(&self.store_key);
+        let prefix_store = tlcs_store.get_immutable_prefix_store(FEE_ESCROW_KEY.to_vec());
+
+        let mut escrows = vec![];
+        for (_, raw) in prefix_store.range(..) {
+            let escrow: RawFeeEscrow = RawFeeEscrow::decode::<Bytes>(raw.into())
+                .expect("invalid data in database - possible database corruption");
+            escrows.push(escrow);
+        }
+
+        escrows
+    }
+
+    /// Returns the canonical keypair of the round, scheme and beacon which can still be shared.
+    /// A canonical keypair which failed, was cancelled or released is replaced by a new one.
+    fn get_canonical_keypair<T: Database>(
+        &self,
+        ctx: &TxContext<T, SK>,
+        round: u64,
+        scheme: u32,
+        beacon_id: u32,
+    ) -> Option<RawMsgKeyPair> {
+        let mut prefix = KEYPAIR_DATA_KEY.to_vec();
+        prefix.append(&mut round.to_be_bytes().to_vec());
+        prefix.append(&mut scheme.to_be_bytes().to_vec());
+
+        ctx.get_kv_store(&self.store_key)
+            .get_immutable_prefix_store(prefix)
+            .range(..)
+            .map(|(_, row)| {
+                RawMsgKeyPair::decode::<Bytes>(row.into())
+                    .expect("invalid data in database - possible database corruption")
+            })
+            .find(|keypair| {
+                keypair.canonical && keypair.beacon_id == beacon_id && !keypair.status().is_final()
+            })
     }
 
     pub fn open_new_process<T: Database>(
@@ -115,31 +1486,60 @@ impl<SK: StoreKey> Keeper<SK> {
         //config: Config,
         msg: &MsgNewProcess,
     ) -> Result<(), AppError> {
-        if msg.round > 0 && valid_scheme(msg.scheme) && check_time(msg.pubkey_time) {
+        let params = self.get_params(ctx);
+        let block_time = self.get_block_time(ctx);
+        self.check_beacon_selectable(ctx, msg.beacon_id)?;
+
+        if msg.round > 0
+            && valid_scheme(msg.scheme)
+            && check_time(msg.pubkey_time, block_time, params.min_pubkey_lead_time)
+        {
             info!(
                 "NEW PROCESS TX: Round: {:?}, Scheme: {:?}",
                 msg.round, msg.scheme
             );
 
-            let keycount = self.open_process_count(ctx, msg.round, msg.scheme);
-
-            let mut store_key = KEYPAIR_DATA_KEY.to_vec();
-            store_key.append(&mut msg.round.to_le_bytes().to_vec());
-            store_key.append(&mut msg.scheme.to_le_bytes().to_vec());
-            store_key.append(&mut keycount.to_le_bytes().to_vec());
-
-            let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
-
-            let key_data: RawMsgKeyPair = RawMsgKeyPair {
-                round: msg.round,
-                scheme: msg.scheme,
-                id: keycount,
-                pubkey_time: msg.pubkey_time,
-                public_key: "".to_string(),
-                private_key: "".to_string(),
-            };
+            if msg.canonical {
+                if let Some(keypair) =
+                    self.get_canonical_keypair(ctx, msg.round, msg.scheme, msg.beacon_id)
+                {
+                    info!(
+                        "NEW PROCESS TX: sharing canonical keypair. Round: {:?}, Scheme: {:?}, Id: {:?}",
+                        keypair.round, keypair.scheme, keypair.id
+                    );
+
+                    // Sharing is free, the keypair is only made once and its fee was paid by the
+                    // first request. The keypair is listed for every address which requested it.
+                    let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
+                    tlcs_store.set(
+                        owner_index_store_key(
+                            msg.address.clone(),
+                            keypair.round,
+                            keypair.scheme,
+                            keypair.id,
+                        ),
+                        vec![1],
+                    );
+                    ctx.push_event(
+                        TimelockEvent::CanonicalKeyPairShared {
+                            keypair: (&keypair).into(),
+                            requester: msg.address.clone(),
+                        }
+                        .into(),
+                    );
+                    return Ok(());
+                }
+            }
 
-            tlcs_store.set(store_key, key_data.encode_to_vec());
+            self.create_keypair(
+                ctx,
+                msg.address.clone(),
+                msg.round,
+                msg.scheme,
+                msg.pubkey_time,
+                msg.canonical,
+                msg.beacon_id,
+            )?;
 
             // Stop sending the keyshares here. It will be handled in the begin blocker
             /*
@@ -185,9 +1585,20 @@ impl<SK: StoreKey> Keeper<SK> {
         ctx: &mut TxContext<T, SK>,
         msg: &MsgMultiNewProcess,
     ) -> Result<(), AppError> {
+        let params = self.get_params(ctx);
+        let block_time = self.get_block_time(ctx);
+        self.check_beacon_selectable(ctx, msg.beacon_id)?;
+
+        if msg.reqnum as usize * msg.schemes.len() > params.max_multi_requests as usize {
+            return Err(AppError::InvalidRequest(format!(
+                "Too many keypairs requested. The maximum is {}",
+                params.max_multi_requests
+            )));
+        }
+
         if msg.startround > 0
             && all_schemes_valid(msg.schemes.clone())
-            && check_time(msg.pubkey_time)
+            && check_time(msg.pubkey_time, block_time, params.min_pubkey_lead_time)
         {
             info!(
                 "NEW MULTI PROCESS TX: Starting Round: {:?}, Schemes: {:?}",
@@ -203,25 +1614,16 @@ impl<SK: StoreKey> Keeper<SK> {
 
             while counter < msg.reqnum {
                 for this_scheme in msg.schemes.clone().into_iter() {
-                    let keycount = self.open_process_count(ctx, this_round, this_scheme);
-
-                    let mut store_key = KEYPAIR_DATA_KEY.to_vec();
-                    store_key.append(&mut this_round.to_le_bytes().to_vec());
-                    store_key.append(&mut this_scheme.to_le_bytes().to_vec());
-
-                    let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
                     let this_pubkey_time = msg.pubkey_time + (counter * 6) as i64;
-
-                    let key_data: RawMsgKeyPair = RawMsgKeyPair {
-                        round: this_round,
-                        scheme: this_scheme,
-                        id: keycount,
-                        pubkey_time: this_pubkey_time,
-                        public_key: "".to_string(),
-                        private_key: "".to_string(),
-                    };
-
-                    tlcs_store.set(store_key, key_data.encode_to_vec());
+                    self.create_keypair(
+                        ctx,
+                        msg.address.clone(),
+                        this_round,
+                        this_scheme,
+                        this_pubkey_time,
+                        false,
+                        msg.beacon_id,
+                    )?;
                 }
 
                 counter += 1;
@@ -236,97 +1638,206 @@ impl<SK: StoreKey> Keeper<SK> {
         Ok(())
     }
 
+    pub fn cancel_key_request<T: Database>(
+        &self,
+        ctx: &mut TxContext<T, SK>,
+        msg: &MsgCancelKeyRequest,
+    ) -> Result<(), AppError> {
+        let store_key = keypair_store_key(msg.round, msg.scheme, msg.id);
+        let mut keypair = match ctx.get_kv_store(&self.store_key).get(&store_key) {
+            Some(raw) => RawMsgKeyPair::decode::<Bytes>(raw.into())
+                .expect("invalid data in database - possible database corruption"),
+            None => return Err(TimelockError::KeyPairNotFound.into()),
+        };
+
+        if keypair.owner != msg.address.to_string() {
+            return Err(TimelockError::NotKeyPairOwner.into());
+        }
+
+        // Other requests which share the keypair would lose it
+        if keypair.canonical {
+            return Err(TimelockError::CanonicalKeyPairNotCancellable.into());
+        }
+
+        // Only keypairs which are still collecting contributions can move to cancelled
+        set_keypair_status(&mut keypair, KeyPairStatus::Cancelled, ctx.get_height())?;
+
+        info!(
+            "CANCEL KEY REQUEST TX: Round: {:?}, Scheme: {:?}, Id: {:?}",
+            msg.round, msg.scheme, msg.id
+        );
+
+        if let Some(escrow) = self.get_fee_escrow(ctx, msg.round, msg.scheme, msg.id) {
+            let payer = escrow.payer.clone();
+            self.release_fee_escrow(ctx, escrow, vec![payer])?;
+        }
+
+        // Without a queue entry no public key is made and no more shares are sent for it
+        let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
+        tlcs_store.delete(&pubkey_queue_store_key(
+            keypair.pubkey_time,
+            keypair.round,
+            keypair.scheme,
+            keypair.id,
+        ));
+        tlcs_store.set(store_key, keypair.encode_to_vec());
+        self.schedule_archiving(ctx, &keypair);
+
+        ctx.push_event(TimelockEvent::KeyRequestCancelled((&keypair).into()).into());
+
+        Ok(())
+    }
+
     pub fn append_contribution<T: Database>(
         &self,
         ctx: &mut TxContext<T, SK>,
         msg: &MsgContribution,
     ) -> Result<(), AppError> {
-        // Make store key
-        let mut store_key = PARTICIPANT_DATA_KEY.to_vec();
-        store_key.append(&mut msg.round.to_le_bytes().to_vec());
-        store_key.append(&mut msg.scheme.to_le_bytes().to_vec());
+        info!(
+            "NEW CONTRIB TX: new data. Round: {:?}, Scheme: {:?}, Id: {:?}",
+            msg.round, msg.scheme, msg.id
+        );
 
-        let addr: Vec<u8> = msg.address.clone().into();
-        store_key.append(&mut addr.to_vec());
+        let mut keypair = match self.check_contribution(ctx, msg) {
+            Ok(keypair) => keypair,
+            Err(e) => {
+                info!("NEW CONTRIB TX: rejected: {}", e);
+                return Err(contribution_rejected(msg, e));
+            }
+        };
 
-        if !valid_scheme(msg.scheme) {
-            return Err(AppError::InvalidRequest("Invalid scheme.".into()));
+        let mut chain_data: RawMsgContribution = msg.to_owned().into();
+        chain_data.submitted_height = ctx.get_height();
+        chain_data.submitted_time = self.get_block_time(ctx);
+        let store_key = contribution_store_key(msg.round, msg.scheme, msg.id, msg.address.clone());
+
+        if keypair.status() == KeyPairStatus::Requested {
+            set_keypair_status(&mut keypair, KeyPairStatus::Collecting, ctx.get_height())?;
+        }
+
+        let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
+        tlcs_store.set(store_key, chain_data.encode_to_vec());
+        tlcs_store.set(
+            keypair_store_key(msg.round, msg.scheme, msg.id),
+            keypair.encode_to_vec(),
+        );
+
+        ctx.push_event(
+            TimelockEvent::ContributionAccepted {
+                keypair: (&keypair).into(),
+                contributor: msg.address.clone(),
+            }
+            .into(),
+        );
+
+        Ok(())
+    }
+
+    /// Returns the keypair the contribution is for if it can be accepted
+    fn check_contribution<T: Database>(
+        &self,
+        ctx: &mut TxContext<T, SK>,
+        msg: &MsgContribution,
+    ) -> Result<RawMsgKeyPair, TimelockError> {
+        if !self.is_registered_contributor(ctx, msg.address.clone()) {
+            return Err(TimelockError::NotRegisteredContributor);
         }
 
-        info!(
-            "NEW CONTRIB TX: new data. Round: {:?}, Scheme: {:?}",
-            msg.round, msg.scheme
-        );
+        // Stored keypairs always have a valid scheme
+        let keypair = match ctx
+            .get_kv_store(&self.store_key)
+            .get(&keypair_store_key(msg.round, msg.scheme, msg.id))
+        {
+            Some(raw) => RawMsgKeyPair::decode::<Bytes>(raw.into())
+                .expect("invalid data in database - possible database corruption"),
+            None => return Err(TimelockError::KeyPairNotFound),
+        };
 
-        let keycount = self.open_process_count(ctx, msg.round, msg.scheme);
+        let block_time = self.get_block_time(ctx);
+        let params = self.get_params(ctx);
 
-        if msg.id <= (keycount - 1) {
-            if verify_keyshare(
-                LOE_PUBLIC_KEY.into(),
-                msg.round,
-                scheme_to_string(msg.scheme),
-                msg.data.clone(),
-                SECURITY_PARAM,
-            ) {
-                //    if verify_participant_data(msg.round, msg.data.clone()) {
-                let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
-                let chain_data: RawMsgContribution = msg.to_owned().into();
-                tlcs_store.set(store_key, chain_data.encode_to_vec());
-            } else {
-                return Err(AppError::InvalidRequest(
-                    "The contribution data is invalid for the given round".into(),
-                ));
-            }
-        } else {
-            return Err(AppError::InvalidRequest(
-                "Can't contribute data without existing keypair request.".into(),
-            ));
+        // A late share must never change a public key which users may already have encrypted to
+        if block_time < keypair.contribution_start {
+            return Err(TimelockError::ContributionWindowNotOpen);
+        }
+        if block_time >= keypair.pubkey_time
+            || !matches!(
+                keypair.status(),
+                KeyPairStatus::Requested | KeyPairStatus::Collecting
+            )
+        {
+            return Err(TimelockError::ContributionWindowClosed);
+        }
+        let store_key = contribution_store_key(msg.round, msg.scheme, msg.id, msg.address.clone());
+        if ctx.get_kv_store(&self.store_key).get(&store_key).is_some() {
+            return Err(TimelockError::DuplicateContribution);
+        }
+        let beacon = self.keypair_beacon(ctx, &keypair);
+        if !beacon.supports_timelock() {
+            return Err(TimelockError::BeaconNotTimelockable);
+        }
+        if !verify_keyshare(
+            beacon.public_key(),
+            msg.round,
+            scheme_to_string(msg.scheme),
+            msg.data.clone(),
+            params.security_param as usize,
+        ) {
+            return Err(TimelockError::InvalidKeyshare);
         }
 
-        Ok(())
+        Ok(keypair)
     }
 
     pub fn query_all_contributions<T: Database>(
         &self,
         ctx: &QueryContext<T, SK>,
+        pagination: Option<PageRequest>,
     ) -> QueryAllContributionsResponse {
         let store_key = PARTICIPANT_DATA_KEY.to_vec();
 
         let tlcs_store = ctx.get_kv_store(&self.store_key);
         let prefix_store = tlcs_store.get_immutable_prefix_store(store_key);
-        let all_raw_data = prefix_store.range(..);
+        let (rows, page) = paginate(&prefix_store, vec![], None, pagination);
 
         let mut contributions = vec![];
 
-        for (_, row) in all_raw_data {
+        for (_, row) in rows {
             let contribution: RawMsgContribution = RawMsgContribution::decode::<Bytes>(row.into())
                 .expect("invalid data in database - possible database corruption");
             contributions.push(contribution);
         }
-        QueryAllContributionsResponse { contributions }
+        QueryAllContributionsResponse {
+            contributions,
+            pagination: Some(page),
+        }
     }
 
     pub fn query_contributions_by_round<T: Database>(
         &self,
         ctx: &QueryContext<T, SK>,
         round: u64,
+        pagination: Option<PageRequest>,
     ) -> QueryAllContributionsResponse {
         let tlcs_store = ctx.get_kv_store(&self.store_key);
 
         let mut store_key = PARTICIPANT_DATA_KEY.to_vec();
-        store_key.append(&mut round.to_le_bytes().to_vec());
+        store_key.append(&mut round.to_be_bytes().to_vec());
 
         let prefix_store = tlcs_store.get_immutable_prefix_store(store_key);
-        let all_raw_data = prefix_store.range(..);
+        let (rows, page) = paginate(&prefix_store, vec![], None, pagination);
 
         let mut contributions = vec![];
 
-        for (_, row) in all_raw_data {
+        for (_, row) in rows {
             let contribution: RawMsgContribution = RawMsgContribution::decode::<Bytes>(row.into())
                 .expect("invalid data in database - possible database corruption");
             contributions.push(contribution);
         }
-        QueryAllContributionsResponse { contributions }
+        QueryAllContributionsResponse {
+            contributions,
+            pagination: Some(page),
+        }
     }
 
     pub fn query_contributions_by_round_and_scheme<T: Database>(
@@ -334,24 +1845,54 @@ impl<SK: StoreKey> Keeper<SK> {
         ctx: &QueryContext<T, SK>,
         round: u64,
         scheme: u32,
+        pagination: Option<PageRequest>,
     ) -> QueryAllContributionsResponse {
         let tlcs_store = ctx.get_kv_store(&self.store_key);
 
         let mut store_key = PARTICIPANT_DATA_KEY.to_vec();
-        store_key.append(&mut round.to_le_bytes().to_vec());
-        store_key.append(&mut scheme.to_le_bytes().to_vec());
+        store_key.append(&mut round.to_be_bytes().to_vec());
+        store_key.append(&mut scheme.to_be_bytes().to_vec());
 
         let prefix_store = tlcs_store.get_immutable_prefix_store(store_key);
-        let all_raw_data = prefix_store.range(..);
+        let (rows, page) = paginate(&prefix_store, vec![], None, pagination);
+
+        let mut contributions = vec![];
+
+        for (_, row) in rows {
+            let contribution: RawMsgContribution = RawMsgContribution::decode::<Bytes>(row.into())
+                .expect("invalid data in database - possible database corruption");
+            contributions.push(contribution);
+        }
+        QueryAllContributionsResponse {
+            contributions,
+            pagination: Some(page),
+        }
+    }
+
+    pub fn query_contributions_by_keypair<T: Database>(
+        &self,
+        ctx: &QueryContext<T, SK>,
+        round: u64,
+        scheme: u32,
+        id: u32,
+        pagination: Option<PageRequest>,
+    ) -> QueryAllContributionsResponse {
+        let tlcs_store = ctx.get_kv_store(&self.store_key);
+        let prefix_store =
+            tlcs_store.get_immutable_prefix_store(keypair_contributions_prefix(round, scheme, id));
+        let (rows, page) = paginate(&prefix_store, vec![], None, pagination);
 
         let mut contributions = vec![];
 
-        for (_, row) in all_raw_data {
+        for (_, row) in rows {
             let contribution: RawMsgContribution = RawMsgContribution::decode::<Bytes>(row.into())
                 .expect("invalid data in database - possible database corruption");
             contributions.push(contribution);
         }
-        QueryAllContributionsResponse { contributions }
+        QueryAllContributionsResponse {
+            contributions,
+            pagination: Some(page),
+        }
     }
 
     // Keypair section
@@ -363,8 +1904,8 @@ impl<SK: StoreKey> Keeper<SK> {
         msg: &MsgKeyPair,
     ) -> Result<(), AppError> {
         let mut prefix = KEYPAIR_DATA_KEY.to_vec();
-        prefix.append(&mut msg.round.to_le_bytes().to_vec());
-        prefix.append(&mut msg.scheme.to_le_bytes().to_vec());
+        prefix.append(&mut msg.round.to_be_bytes().to_vec());
+        prefix.append(&mut msg.scheme.to_be_bytes().to_vec());
 
         if !valid_scheme(msg.scheme) {
             return Err(AppError::InvalidRequest("Invalid scheme.".into()));
@@ -381,67 +1922,249 @@ impl<SK: StoreKey> Keeper<SK> {
     pub fn query_all_keypairs<T: Database>(
         &self,
         ctx: &QueryContext<T, SK>,
+        pagination: Option<PageRequest>,
     ) -> QueryAllKeyPairsResponse {
         let tlcs_store = ctx.get_kv_store(&self.store_key);
         let store_key = KEYPAIR_DATA_KEY.to_vec();
 
         let prefix_store = tlcs_store.get_immutable_prefix_store(store_key);
-        let all_raw_data = prefix_store.range(..);
+        let (rows, page) = paginate(&prefix_store, vec![], None, pagination);
 
         let mut keypairs = vec![];
 
-        for (_, row) in all_raw_data {
+        for (_, row) in rows {
             let keypair: RawMsgKeyPair = RawMsgKeyPair::decode::<Bytes>(row.into())
                 .expect("invalid data in database - possible database corruption");
             keypairs.push(keypair);
         }
-        QueryAllKeyPairsResponse { keypairs }
+        QueryAllKeyPairsResponse {
+            keypairs,
+            pagination: Some(page),
+        }
     }
 
     pub fn query_keypairs_by_round<T: Database>(
         &self,
         ctx: &QueryContext<T, SK>,
         round: u64,
+        pagination: Option<PageRequest>,
     ) -> QueryAllKeyPairsResponse {
         let tlcs_store = ctx.get_kv_store(&self.store_key);
         let mut store_key = KEYPAIR_DATA_KEY.to_vec();
-        store_key.append(&mut round.to_le_bytes().to_vec());
+        store_key.append(&mut round.to_be_bytes().to_vec());
 
         let prefix_store = tlcs_store.get_immutable_prefix_store(store_key);
-        let all_raw_data = prefix_store.range(..);
+        let (rows, page) = paginate(&prefix_store, vec![], None, pagination);
 
         let mut keypairs = vec![];
 
-        for (_, row) in all_raw_data {
+        for (_, row) in rows {
             let keypair: RawMsgKeyPair = RawMsgKeyPair::decode::<Bytes>(row.into())
                 .expect("invalid data in database - possible database corruption");
             keypairs.push(keypair);
         }
-        QueryAllKeyPairsResponse { keypairs }
+        QueryAllKeyPairsResponse {
+            keypairs,
+            pagination: Some(page),
+        }
     }
 
+    /// Returns the keypairs of the round each beacon publishes at the given unix time
     pub fn query_keypairs_by_time<T: Database>(
         &self,
         ctx: &QueryContext<T, SK>,
         time: i64,
+        pagination: Option<PageRequest>,
     ) -> QueryAllKeyPairsResponse {
-        let tlcs_store = ctx.get_kv_store(&self.store_key);
-        let mut store_key = KEYPAIR_DATA_KEY.to_vec();
+        self.query_keypairs_by_beacon_rounds(
+            ctx,
+            |beacon| {
+                let round = beacon.round_at(time);
+                (round, round)
+            },
+            pagination,
+        )
+    }
 
-        let latest_round = (time as u32 - LOE_GENESIS_TIME) / LOE_PERIOD;
+    /// Returns the keypairs of the rounds from `from` to `to`, both included
+    pub fn query_keypairs_by_round_range<T: Database>(
+        &self,
+        ctx: &QueryContext<T, SK>,
+        from: u64,
+        to: u64,
+        pagination: Option<PageRequest>,
+    ) -> QueryAllKeyPairsResponse {
+        let tlcs_store = ctx.get_kv_store(&self.store_key);
+        let prefix_store = tlcs_store.get_immutable_prefix_store(KEYPAIR_DATA_KEY.to_vec());
 
-        store_key.append(&mut latest_round.to_le_bytes().to_vec());
-        let prefix_store = tlcs_store.get_immutable_prefix_store(store_key);
-        let all_raw_data = prefix_store.range(..);
+        let end = to.checked_add(1).map(|end| end.to_be_bytes().to_vec());
+        let (rows, page) = paginate(&prefix_store, from.to_be_bytes().to_vec(), end, pagination);
 
         let mut keypairs = vec![];
 
-        for (_, row) in all_raw_data {
+        for (_, row) in rows {
             let keypair: RawMsgKeyPair = RawMsgKeyPair::decode::<Bytes>(row.into())
                 .expect("invalid data in database - possible database corruption");
             keypairs.push(keypair);
         }
-        QueryAllKeyPairsResponse { keypairs }
+        QueryAllKeyPairsResponse {
+            keypairs,
+            pagination: Some(page),
+        }
+    }
+
+    /// Returns the keypairs of the rounds their beacon publishes from `from_unix` to `to_unix`,
+    /// both included
+    pub fn query_keypairs_by_time_range<T: Database>(
+        &self,
+        ctx: &QueryContext<T, SK>,
+        from_unix: i64,
+        to_unix: i64,
+        pagination: Option<PageRequest>,
+    ) -> QueryAllKeyPairsResponse {
+        self.query_keypairs_by_beacon_rounds(
+            ctx,
+            |beacon| {
+                (
+                    beacon.round_at(from_unix.saturating_sub(1)) + 1,
+                    beacon.round_at(to_unix),
+                )
+            },
+            pagination,
+        )
+    }
+
+    /// Returns a page of the keypairs whose rounds are in the range `rounds` gives for their
+    /// beacon, both ends included. The beacons are read in id order and a page key is the
+    /// beacon id followed by the keypair key.
+    fn query_keypairs_by_beacon_rounds<T: Database>(
+        &self,
+        ctx: &QueryContext<T, SK>,
+        rounds: impl Fn(&dyn Beacon) -> (u64, u64),
+        pagination: Option<PageRequest>,
+    ) -> QueryAllKeyPairsResponse {
+        let page = pagination.unwrap_or_default();
+        let limit = page_limit(&page);
+        let (start_beacon, start_key) = match page.key.get(..4) {
+            Some(id) => (
+                u32::from_be_bytes(id.try_into().expect("slice is 4 bytes")),
+                page.key[4..].to_vec(),
+            ),
+            None => (0, vec![]),
+        };
+
+        let tlcs_store = ctx.get_kv_store(&self.store_key);
+        let beacons: Vec<RawBeacon> = tlcs_store
+            .get_immutable_prefix_store(BEACON_KEY.to_vec())
+            .range(start_beacon.to_be_bytes().to_vec()..)
+            .map(|(_, raw)| {
+                RawBeacon::decode::<Bytes>(raw.into())
+                    .expect("invalid data in database - possible database corruption")
+            })
+            .collect();
+        let prefix_store = tlcs_store.get_immutable_prefix_store(KEYPAIR_DATA_KEY.to_vec());
+
+        let mut keypairs = vec![];
+        let mut next_key = vec![];
+        let mut skip = page.offset as usize;
+
+        'beacons: for entry in beacons {
+            let (from, to) = match registered_beacon(&entry) {
+                Some(beacon) => rounds(beacon.as_ref()),
+                None => continue,
+            };
+            if to < from {
+                continue;
+            }
+
+            let mut start = from.to_be_bytes().to_vec();
+            if entry.id == start_beacon && start_key > start {
+                start = start_key.clone();
+            }
+            let end = to.checked_add(1).map(|end| end.to_be_bytes().to_vec());
+
+            for (key, row) in prefix_store.range(start..) {
+                if end.as_ref().map_or(false, |end| &key >= end) {
+                    break;
+                }
+
+                let keypair = RawMsgKeyPair::decode::<Bytes>(row.into())
+                    .expect("invalid data in database - possible database corruption");
+                if keypair.beacon_id != entry.id {
+                    continue;
+                }
+                if skip > 0 {
+                    skip -= 1;
+                    continue;
+                }
+                if keypairs.len() == limit {
+                    next_key = entry.id.to_be_bytes().to_vec();
+                    next_key.append(&mut key.clone());
+                    break 'beacons;
+                }
+
+                keypairs.push(keypair);
+            }
+        }
+
+        QueryAllKeyPairsResponse {
+            keypairs,
+            pagination: Some(PageResponse { next_key }),
+        }
+    }
+
+    pub fn query_keypair<T: Database>(
+        &self,
+        ctx: &QueryContext<T, SK>,
+        round: u64,
+        scheme: u32,
+        id: u32,
+    ) -> Result<QueryKeyPairResponse, AppError> {
+        let keypair = ctx
+            .get_kv_store(&self.store_key)
+            .get(&keypair_store_key(round, scheme, id))
+            .map(|raw| {
+                RawMsgKeyPair::decode::<Bytes>(raw.into())
+                    .expect("invalid data in database - possible database corruption")
+            })
+            .ok_or(TimelockError::KeyPairNotFound)?;
+
+        Ok(QueryKeyPairResponse {
+            keypair: Some(keypair),
+        })
+    }
+
+    /// Keypairs requested by the address, including the canonical keypairs it shares
+    pub fn query_keypairs_by_owner<T: Database>(
+        &self,
+        ctx: &QueryContext<T, SK>,
+        owner: AccAddress,
+        pagination: Option<PageRequest>,
+    ) -> QueryAllKeyPairsResponse {
+        let tlcs_store = ctx.get_kv_store(&self.store_key);
+
+        let prefix_store = tlcs_store.get_immutable_prefix_store(owner_index_prefix(owner));
+        let (rows, page) = paginate(&prefix_store, vec![], None, pagination);
+
+        let mut keypairs = vec![];
+
+        // The index key is the keypair key
+        for (mut key, _) in rows {
+            let mut store_key = KEYPAIR_DATA_KEY.to_vec();
+            store_key.append(&mut key);
+
+            // Pruned keypairs are only kept in the archive
+            if let Some(row) = tlcs_store.get(&store_key) {
+                let keypair: RawMsgKeyPair = RawMsgKeyPair::decode::<Bytes>(row.into())
+                    .expect("invalid data in database - possible database corruption");
+                keypairs.push(keypair);
+            }
+        }
+
+        QueryAllKeyPairsResponse {
+            keypairs,
+            pagination: Some(page),
+        }
     }
 
     pub fn query_keypairs_by_round_and_scheme<T: Database>(
@@ -449,43 +2172,60 @@ impl<SK: StoreKey> Keeper<SK> {
         ctx: &QueryContext<T, SK>,
         round: u64,
         scheme: u32,
+        pagination: Option<PageRequest>,
     ) -> QueryAllKeyPairsResponse {
         let tlcs_store = ctx.get_kv_store(&self.store_key);
 
         let mut store_key = KEYPAIR_DATA_KEY.to_vec();
-        store_key.append(&mut round.to_le_bytes().to_vec());
-        store_key.append(&mut scheme.to_le_bytes().to_vec());
+        store_key.append(&mut round.to_be_bytes().to_vec());
+        store_key.append(&mut scheme.to_be_bytes().to_vec());
 
         let prefix_store = tlcs_store.get_immutable_prefix_store(store_key);
-        let all_raw_data = prefix_store.range(..);
+        let (rows, page) = paginate(&prefix_store, vec![], None, pagination);
 
         let mut keypairs = vec![];
 
-        for (_, row) in all_raw_data {
+        for (_, row) in rows {
             let keypair: RawMsgKeyPair = RawMsgKeyPair::decode::<Bytes>(row.into())
                 .expect("invalid data in database - possible database corruption");
             keypairs.push(keypair);
         }
 
-        QueryAllKeyPairsResponse { keypairs }
+        QueryAllKeyPairsResponse {
+            keypairs,
+            pagination: Some(page),
+        }
     }
 
     pub fn append_loe_data<T: Database>(
         &self,
-        ctx: &mut Context<T, SK>,
+        ctx: &mut TxContext<T, SK>,
         msg: &MsgLoeData,
     ) -> Result<(), AppError> {
-        let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
-
-        let mut store_key = LOE_DATA_KEY.to_vec();
-        store_key.append(&mut msg.round.to_le_bytes().to_vec());
+        // Retired beacons still release the keypairs which were requested for them
+        let beacon = match self.get_beacon(ctx, msg.beacon_id) {
+            Some(beacon) => registered_beacon(&beacon)
+                .expect("invalid data in database - possible database corruption"),
+            None => return Err(TimelockError::BeaconNotFound.into()),
+        };
 
-        if loe_signature_is_valid(msg.round, msg.signature.clone(), LOE_PUBLIC_KEY.into()) {
-            info!("TX LOE Data stored: Round: {:?}", msg.round);
+        if beacon.verify(msg.round, &msg.previous_signature, &msg.signature) {
+            info!(
+                "TX LOE Data stored: Beacon: {:?}, Round: {:?}",
+                msg.beacon_id, msg.round
+            );
+            let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
             tlcs_store.set(
-                store_key,
+                loe_data_store_key(msg.beacon_id, msg.round),
                 <MsgLoeData as Into<RawMsgLoeData>>::into(msg.to_owned()).encode_to_vec(),
             );
+            ctx.push_event(
+                TimelockEvent::LoeDataStored {
+                    beacon_id: msg.beacon_id,
+                    round: msg.round,
+                }
+                .into(),
+            );
         } else {
             info!("TX LOE Data rejected: Round: {:?}", msg.round);
             return Err(AppError::InvalidRequest(format!(
@@ -500,43 +2240,55 @@ impl<SK: StoreKey> Keeper<SK> {
     pub fn query_all_loe_data<T: Database>(
         &self,
         ctx: &QueryContext<T, SK>,
+        pagination: Option<PageRequest>,
     ) -> QueryAllLoeDataResponse {
         let tlcs_store = ctx.get_kv_store(&self.store_key);
         let store_key = LOE_DATA_KEY.to_vec();
         let prefix_store = tlcs_store.get_immutable_prefix_store(store_key);
-        let all_raw_data = prefix_store.range(..);
+        let (rows, page) = paginate(&prefix_store, vec![], None, pagination);
 
         let mut randomnesses = vec![];
 
-        for (_, row) in all_raw_data {
+        for (_, row) in rows {
             let loe_data: RawMsgLoeData = RawMsgLoeData::decode::<Bytes>(row.into())
                 .expect("invalid data in database - possible database corruption");
             randomnesses.push(loe_data);
         }
 
-        QueryAllLoeDataResponse { randomnesses }
+        QueryAllLoeDataResponse {
+            randomnesses,
+            pagination: Some(page),
+        }
     }
 
+    /// Returns the LOE data of the round from every beacon which has it. The page is a page
+    /// of the registered beacons.
     pub fn query_loe_data_by_round<T: Database>(
         &self,
         ctx: &QueryContext<T, SK>,
         round: u64,
+        pagination: Option<PageRequest>,
     ) -> QueryAllLoeDataResponse {
         let tlcs_store = ctx.get_kv_store(&self.store_key);
-        let mut store_key = KEYPAIR_DATA_KEY.to_vec();
-        store_key.append(&mut round.to_le_bytes().to_vec());
-        let prefix_store = tlcs_store.get_immutable_prefix_store(store_key);
-        let all_raw_data = prefix_store.range(..);
+        let prefix_store = tlcs_store.get_immutable_prefix_store(BEACON_KEY.to_vec());
+        let (rows, page) = paginate(&prefix_store, vec![], None, pagination);
 
         let mut randomnesses = vec![];
 
-        for (_, row) in all_raw_data {
-            let rand: RawMsgLoeData = RawMsgLoeData::decode::<Bytes>(row.into())
+        for (_, row) in rows {
+            let beacon: RawBeacon = RawBeacon::decode::<Bytes>(row.into())
                 .expect("invalid data in database - possible database corruption");
-            randomnesses.push(rand);
+            if let Some(row) = tlcs_store.get(&loe_data_store_key(beacon.id, round)) {
+                let rand: RawMsgLoeData = RawMsgLoeData::decode::<Bytes>(row.into())
+                    .expect("invalid data in database - possible database corruption");
+                randomnesses.push(rand);
+            }
         }
 
-        QueryAllLoeDataResponse { randomnesses }
+        QueryAllLoeDataResponse {
+            randomnesses,
+            pagination: Some(page),
+        }
     }
 
     pub fn make_keyshares<T: Database>(
@@ -544,60 +2296,54 @@ impl<SK: StoreKey> Keeper<SK> {
         ctx: &mut TxContext<T, SK>,
         config: Config,
     ) -> Result<()> {
-        let mut list_of_key_requests: BTreeMap<Vec<u8>, RawMsgKeyPair> = BTreeMap::new();
-        let mut list_of_contrib_data: BTreeMap<Vec<u8>, RawMsgContribution> = BTreeMap::new();
+        let mut list_of_key_requests: BTreeMap<(u64, u32, u32), RawMsgKeyPair> = BTreeMap::new();
+        let mut list_of_contrib_data: BTreeMap<(u64, u32, u32), RawMsgContribution> =
+            BTreeMap::new();
 
+        let params = self.get_params(ctx);
+        let block_time = self.get_block_time(ctx);
         let tlcs_store = ctx.get_kv_store(&self.store_key);
 
-        let keypair_store_key = KEYPAIR_DATA_KEY.to_vec();
-        let prefix_store = tlcs_store.get_immutable_prefix_store(keypair_store_key);
-        let keypairs = prefix_store.range(..);
-
-        // Get all keypairs that have a blank public key
-        for (_, keypair) in keypairs {
-            let the_keys: RawMsgKeyPair = RawMsgKeyPair::decode::<Bytes>(keypair.into())
-                .expect("invalid data in database - possible database corruption");
-            if the_keys.public_key.is_empty() {
-                let mut collection_index = PARTICIPANT_DATA_KEY.to_vec();
-                collection_index.append(&mut the_keys.round.to_le_bytes().to_vec());
-                collection_index.append(&mut the_keys.scheme.to_le_bytes().to_vec());
+        // Get all keypairs that are still accepting contributions
+        let queue: Vec<Vec<u8>> = tlcs_store
+            .get_immutable_prefix_store(PUBKEY_QUEUE_KEY.to_vec())
+            .range((block_time.max(0) as u64 + 1).to_be_bytes().to_vec()..)
+            .map(|(key, _)| key)
+            .collect();
 
-                list_of_key_requests.insert(collection_index, the_keys);
-                // Needed fields
-                // round, scheme, id
-            }
+        for queue_key in queue {
+            let the_keys = self.get_queued_keypair(ctx, &queue_key[8..]);
+            list_of_key_requests.insert((the_keys.round, the_keys.scheme, the_keys.id), the_keys);
         }
 
-        // Get list of contrib data from this node user
-        let contrib_store_key = PARTICIPANT_DATA_KEY.to_vec();
-        let cdata_store = tlcs_store.get_immutable_prefix_store(contrib_store_key);
-        let cdata_range = cdata_store.range(..);
-
         let key_store: DiskStore<Secp256k1KeyPair> = DiskStore::new(config.home.clone())?;
         let key = key_store.get_key(&config.from)?;
         let myaddress = AccAddress::from_str(&key.account())?;
+
+        // Contributions from unregistered addresses would be rejected
+        if !self.is_registered_contributor(ctx, myaddress.clone()) {
+            return Ok(());
+        }
         //let address = AccAddress::from_bech32(&raw.address)
         //    .map_err(|e| Error::DecodeAddress(e.to_string()))?;
         //let account = get_account_latest(address.clone(), node.clone())?;
 
-        for (_, cdata) in cdata_range {
-            let the_data: RawMsgContribution = RawMsgContribution::decode::<Bytes>(cdata.into())
-                .expect("invalid data in database - possible database corruption");
-            if the_data.address == myaddress.to_string() {
-                let mut contrib_index = PARTICIPANT_DATA_KEY.to_vec();
-                contrib_index.append(&mut the_data.round.to_le_bytes().to_vec());
-                contrib_index.append(&mut the_data.scheme.to_le_bytes().to_vec());
-
-                list_of_contrib_data.insert(contrib_index, the_data);
+        // Get list of contrib data from this node user
+        let tlcs_store = ctx.get_kv_store(&self.store_key);
+        for (round, scheme, id) in list_of_key_requests.keys() {
+            let store_key = contribution_store_key(*round, *scheme, *id, myaddress.clone());
+            if let Some(cdata) = tlcs_store.get(&store_key) {
+                let the_data: RawMsgContribution =
+                    RawMsgContribution::decode::<Bytes>(cdata.into())
+                        .expect("invalid data in database - possible database corruption");
+                list_of_contrib_data.insert((*round, *scheme, *id), the_data);
             }
         }
 
         // For the first keypair that we haven't contributed data to, send a contribution
         for (index, value) in list_of_key_requests.iter() {
-            // TODO: Have to also check scheme
-            // the contrib data will be there for the other scheme and so no new data will be
-            // generated
-            if !list_of_contrib_data.contains_key(index) {
+            let beacon = self.keypair_beacon(ctx, value);
+            if !list_of_contrib_data.contains_key(index) && beacon.supports_timelock() {
                 let mut contrib_to_send = MsgContribution {
                     address: myaddress.clone(),
                     round: value.round,
@@ -607,15 +2353,15 @@ impl<SK: StoreKey> Keeper<SK> {
                 };
 
                 contrib_to_send.data = make_keyshare(
-                    LOE_PUBLIC_KEY.into(),
+                    beacon.public_key(),
                     contrib_to_send.round,
                     scheme_to_string(contrib_to_send.scheme),
-                    SECURITY_PARAM,
+                    params.security_param as usize,
                 );
 
                 info!(
-                    "MAKE_KEYSHARES: sending contribution for round: {:?}, scheme: {:?}",
-                    contrib_to_send.round, contrib_to_send.scheme
+                    "MAKE_KEYSHARES: sending contribution for round: {:?}, scheme: {:?}, id: {:?}",
+                    contrib_to_send.round, contrib_to_send.scheme, contrib_to_send.id
                 );
 
                 thread::spawn(move || {
@@ -640,50 +2386,143 @@ impl<SK: StoreKey> Keeper<SK> {
         Ok(())
     }
 
+    /// Returns the keypairs whose public key time has passed and the keypairs whose LOE data is
+    /// available. Only the pending work queues are read, so the cost doesn't grow with the number
+    /// of stored keypairs. At most `max_key_derivations_per_block` keypairs are returned, each in
+    /// queue order. Public and private keys get half of the budget each and the share one of them
+    /// doesn't use goes to the other, so neither can starve the other. The work which doesn't fit
+    /// is left for the next blocks.
     pub fn get_empty_keypairs<T: Database>(
         &self,
         ctx: &mut TxContext<T, SK>,
-    ) -> (
-        HashMap<Vec<u8>, RawMsgKeyPair>,
-        HashMap<Vec<u8>, RawMsgKeyPair>,
-    ) {
-        let mut need_pub_key: HashMap<Vec<u8>, RawMsgKeyPair> = HashMap::new();
-        let mut need_priv_key: HashMap<Vec<u8>, RawMsgKeyPair> = HashMap::new();
+        block_time: i64,
+    ) -> (Vec<(Vec<u8>, RawMsgKeyPair)>, Vec<(Vec<u8>, RawMsgKeyPair)>) {
+        let mut need_pub_key: Vec<(Vec<u8>, RawMsgKeyPair)> = vec![];
 
+        let params = self.get_params(ctx);
         let tlcs_store = ctx.get_kv_store(&self.store_key);
-        let store_key = KEYPAIR_DATA_KEY.to_vec();
-        let prefix_store = tlcs_store.get_immutable_prefix_store(store_key);
-        let keypairs = prefix_store.range(..);
 
-        for (index, keypair) in keypairs {
-            let the_keys: RawMsgKeyPair = RawMsgKeyPair::decode::<Bytes>(keypair.into())
-                .expect("invalid data in database - possible database corruption");
-            if the_keys.public_key.is_empty() {
-                need_pub_key.insert(index, the_keys);
-            } else if the_keys.private_key.is_empty() {
-                need_priv_key.insert(index, the_keys);
+        let pubkey_queue: Vec<Vec<u8>> = tlcs_store
+            .get_immutable_prefix_store(PUBKEY_QUEUE_KEY.to_vec())
+            .range(..(block_time.max(0) as u64).to_be_bytes().to_vec())
+            .map(|(key, _)| key)
+            .collect();
+
+        // Rounds after the current round of a beacon can't have data yet
+        let mut loe_queue: Vec<Vec<u8>> = vec![];
+        for beacon in self.get_beacons(ctx) {
+            let loe_round = registered_beacon(&beacon)
+                .expect("invalid data in database - possible database corruption")
+                .round_at(block_time);
+
+            let mut prefix = LOE_QUEUE_KEY.to_vec();
+            prefix.append(&mut beacon.id.to_be_bytes().to_vec());
+            loe_queue.extend(
+                tlcs_store
+                    .get_immutable_prefix_store(prefix)
+                    .range(..(loe_round + 1).to_be_bytes().to_vec())
+                    .map(|(key, _)| key),
+            );
+        }
+
+        let mut ready_priv_keys: Vec<(Vec<u8>, RawMsgKeyPair)> = vec![];
+        for queue_key in loe_queue {
+            // Within the beacon's queue the key is the keypair key
+            let mut keypair = self.get_queued_keypair(ctx, &queue_key);
+            if self
+                .get_this_round_loe_signature(ctx, keypair.beacon_id, keypair.round)
+                .is_none()
+            {
+                // The round was reached, so its data is overdue
+                if keypair.status() == KeyPairStatus::PublicKeyReady {
+                    set_keypair_status(
+                        &mut keypair,
+                        KeyPairStatus::AwaitingBeacon,
+                        ctx.get_height(),
+                    )
+                    .expect("invalid keypair status in database - possible database corruption");
+
+                    let mut store_key = KEYPAIR_DATA_KEY.to_vec();
+                    store_key.append(&mut queue_key.clone());
+                    ctx.get_mutable_kv_store(&self.store_key)
+                        .set(store_key, keypair.encode_to_vec());
+                }
+                continue;
             }
+
+            ready_priv_keys.push((queue_key, keypair));
         }
 
-        (need_pub_key, need_priv_key)
+        // Private keys get at least half of the budget, public keys get the rest
+        let budget = params.max_key_derivations_per_block as usize;
+        let priv_budget = ready_priv_keys
+            .len()
+            .min((budget / 2).max(budget.saturating_sub(pubkey_queue.len())));
+        let pub_budget = pubkey_queue.len().min(budget - priv_budget);
+
+        let pending = QueryPendingWorkResponse {
+            public_keys: (pubkey_queue.len() - pub_budget) as u64,
+            private_keys: (ready_priv_keys.len() - priv_budget) as u64,
+            height: ctx.get_height(),
+        };
+
+        for queue_key in pubkey_queue.into_iter().take(pub_budget) {
+            // The queue key is the public key time followed by the keypair key
+            let index = queue_key[8..].to_vec();
+            let keypair = self.get_queued_keypair(ctx, &index);
+            need_pub_key.push((index, keypair));
+        }
+
+        ready_priv_keys.truncate(priv_budget);
+
+        let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
+        tlcs_store.set(PENDING_WORK_KEY.to_vec(), pending.encode_to_vec());
+
+        (need_pub_key, ready_priv_keys)
+    }
+
+    fn get_queued_keypair<T: Database>(
+        &self,
+        ctx: &TxContext<T, SK>,
+        index: &[u8],
+    ) -> RawMsgKeyPair {
+        let mut store_key = KEYPAIR_DATA_KEY.to_vec();
+        store_key.append(&mut index.to_vec());
+
+        let raw = ctx
+            .get_kv_store(&self.store_key)
+            .get(&store_key)
+            .expect("invalid data in database - possible database corruption");
+        RawMsgKeyPair::decode::<Bytes>(raw.into())
+            .expect("invalid data in database - possible database corruption")
     }
 
     pub fn make_public_keys<T: Database>(
         &self,
         ctx: &mut TxContext<T, SK>,
-        new_key_list: HashMap<Vec<u8>, RawMsgKeyPair>,
+        new_key_list: Vec<(Vec<u8>, RawMsgKeyPair)>,
         cur_time: i64,
         contribution_threshold: u32,
     ) {
-        let mut tmp_store: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+        // Keypairs are written in the order of the pubkey queue so every node does the same writes
+        let mut tmp_store: Vec<(Vec<u8>, Vec<u8>)> = vec![];
+
+        // The floor is never lowered to the active set, keys which need more trust than the active
+        // contributors can give fail instead
+        let reachable = contribution_threshold <= self.active_contributor_count(ctx);
 
         for (key, mut keypair) in new_key_list {
             let mut all_participant_data: Vec<Vec<u8>> = vec![];
+            let mut contributors: Vec<String> = vec![];
             let mut contrib_count: u32 = 0;
 
             if keypair.pubkey_time < cur_time {
-                let round_all_participant_data =
-                    self.get_this_round_all_participant_data(ctx, keypair.round, keypair.scheme);
+                let round_all_participant_data = self.get_keypair_participant_data(
+                    ctx,
+                    keypair.round,
+                    keypair.scheme,
+                    keypair.id,
+                );
 
                 for (_, row) in round_all_participant_data {
                     let contribution: RawMsgContribution =
@@ -691,16 +2530,81 @@ impl<SK: StoreKey> Keeper<SK> {
                             .expect("invalid data in database - possible database corruption");
 
                     all_participant_data.push(contribution.data);
+                    contributors.push(contribution.address);
                     contrib_count += 1;
                 }
 
-                if contrib_count > contribution_threshold {
+                let escrow = self.get_fee_escrow(ctx, keypair.round, keypair.scheme, keypair.id);
+
+                let status = if reachable && contrib_count >= contribution_threshold {
+                    KeyPairStatus::PublicKeyReady
+                } else {
+                    // The contribution window is closed so the threshold can't be reached anymore
+                    KeyPairStatus::Failed
+                };
+                if let Err(e) = set_keypair_status(&mut keypair, status, ctx.get_height()) {
+                    info!(
+                        "MAKE_PK: keypair round: {:?}, scheme: {:?}, id: {:?} can't move from {:?} to {:?}, failing it: {:?}",
+                        keypair.round,
+                        keypair.scheme,
+                        keypair.id,
+                        keypair.status(),
+                        status,
+                        e
+                    );
+                    force_keypair_failed(&mut keypair, ctx.get_height());
+                }
+
+                if keypair.status() == KeyPairStatus::PublicKeyReady {
                     info!("MAKE_PK: making key for round: {:?}", keypair.round);
                     keypair.public_key =
                         make_public_key(scheme_to_string(keypair.scheme), &all_participant_data);
+                    keypair.contribution_threshold = contribution_threshold;
+                    ctx.push_event(TimelockEvent::PublicKeyFinalized((&keypair).into()).into());
+
+                    if let Some(escrow) = escrow {
+                        if let Err(e) = self.release_fee_escrow(ctx, escrow, contributors.clone()) {
+                            info!(
+                                "MAKE_PK: fee not fully paid, the rest stays escrowed: {:?}",
+                                e
+                            );
+                        }
+                    }
+                } else {
+                    info!("MAKE_PK: request failed for round: {:?}", keypair.round);
+                    ctx.push_event(TimelockEvent::KeyPairFailed((&keypair).into()).into());
+                    self.schedule_archiving(ctx, &keypair);
+
+                    if let Some(escrow) = escrow {
+                        let payer = escrow.payer.clone();
+                        if let Err(e) = self.release_fee_escrow(ctx, escrow, vec![payer]) {
+                            info!("MAKE_PK: fee not refunded, it stays escrowed: {:?}", e);
+                        }
+                    }
+                }
 
-                    tmp_store.insert(key, keypair.encode_to_vec());
+                self.record_participation(ctx, &keypair, &contributors);
+
+                let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
+                tlcs_store.delete(&pubkey_queue_store_key(
+                    keypair.pubkey_time,
+                    keypair.round,
+                    keypair.scheme,
+                    keypair.id,
+                ));
+                if keypair.status() == KeyPairStatus::PublicKeyReady {
+                    tlcs_store.set(
+                        loe_queue_store_key(
+                            keypair.beacon_id,
+                            keypair.round,
+                            keypair.scheme,
+                            keypair.id,
+                        ),
+                        vec![1],
+                    );
                 }
+
+                tmp_store.push((key, keypair.encode_to_vec()));
             }
         }
 
@@ -716,15 +2620,15 @@ impl<SK: StoreKey> Keeper<SK> {
     pub fn make_secret_keys<T: Database>(
         &self,
         ctx: &mut TxContext<T, SK>,
-        new_key_list: HashMap<Vec<u8>, RawMsgKeyPair>,
+        new_key_list: Vec<(Vec<u8>, RawMsgKeyPair)>,
     ) {
-        let mut tmp_store: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+        let mut released: Vec<(Vec<u8>, RawMsgKeyPair)> = vec![];
         let mut loe_signature: String;
 
         for (key, mut keypair) in new_key_list {
             let mut all_participant_data: Vec<Vec<u8>> = vec![];
 
-            match self.get_this_round_loe_signature(ctx, keypair.round) {
+            match self.get_this_round_loe_signature(ctx, keypair.beacon_id, keypair.round) {
                 Some(data) => {
                     loe_signature = data;
                 }
@@ -732,7 +2636,7 @@ impl<SK: StoreKey> Keeper<SK> {
             }
 
             let round_all_participant_data =
-                self.get_this_round_all_participant_data(ctx, keypair.round, keypair.scheme);
+                self.get_keypair_participant_data(ctx, keypair.round, keypair.scheme, keypair.id);
 
             for (_, row) in round_all_participant_data {
                 let contribution: RawMsgContribution =
@@ -747,56 +2651,226 @@ impl<SK: StoreKey> Keeper<SK> {
                 loe_signature,
                 all_participant_data,
             );
+            set_keypair_status(&mut keypair, KeyPairStatus::Released, ctx.get_height())
+                .expect("invalid keypair status in database - possible database corruption");
+            ctx.push_event(TimelockEvent::SecretKeyReleased((&keypair).into()).into());
 
-            tmp_store.insert(key, keypair.encode_to_vec());
+            released.push((key, keypair));
         }
 
+        let params = self.get_params(ctx);
+        let prune_height = ctx.get_height() + params.retention_blocks;
+
         let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
-        for (mut k, v) in tmp_store {
+        for (key, keypair) in released {
+            tlcs_store.delete(&loe_queue_store_key(
+                keypair.beacon_id,
+                keypair.round,
+                keypair.scheme,
+                keypair.id,
+            ));
+
+            if params.retention_blocks > 0 {
+                tlcs_store.set(
+                    released_queue_store_key(
+                        prune_height,
+                        keypair.round,
+                        keypair.scheme,
+                        keypair.id,
+                    ),
+                    vec![1],
+                );
+            }
+
             let mut prefix = KEYPAIR_DATA_KEY.to_vec();
-            prefix.append(&mut k);
-            tlcs_store.set(prefix, v)
+            prefix.append(&mut key.clone());
+            tlcs_store.set(prefix, keypair.encode_to_vec())
         }
     }
 
-    #[allow(dead_code)]
-    pub fn get_contribution_threshold<T: Database>(&self, ctx: &mut TxContext<T, SK>) -> u32 {
+    /// Queues a keypair which reached a final status to be archived once its retention period
+    /// ended. Released keypairs are queued with the other keypairs released in the block.
+    fn schedule_archiving<T: Database>(&self, ctx: &mut TxContext<T, SK>, keypair: &RawMsgKeyPair) {
+        let params = self.get_params(ctx);
+        if params.retention_blocks == 0 {
+            return;
+        }
+
+        let prune_height = ctx.get_height() + params.retention_blocks;
+        ctx.get_mutable_kv_store(&self.store_key).set(
+            released_queue_store_key(prune_height, keypair.round, keypair.scheme, keypair.id),
+            vec![1],
+        );
+    }
+
+    /// Archives the released, failed and cancelled keypairs whose retention period ended,
+    /// removing their contributions and the LOE data which isn't needed anymore. At most
+    /// `max_prunes_per_block` keypairs are archived per block, the rest is left for the next
+    /// blocks.
+    pub fn prune_final_keypairs<T: Database>(&self, ctx: &mut TxContext<T, SK>) {
+        let params = self.get_params(ctx);
+        let height = ctx.get_height();
+
+        let due: Vec<Vec<u8>> = ctx
+            .get_kv_store(&self.store_key)
+            .get_immutable_prefix_store(RELEASED_QUEUE_KEY.to_vec())
+            .range(..(height + 1).to_be_bytes().to_vec())
+            .take(params.max_prunes_per_block as usize)
+            .map(|(key, _)| key)
+            .collect();
+
+        if due.is_empty() {
+            return;
+        }
+
+        let mut stats = self.get_prune_stats(ctx);
+
+        for queue_key in due {
+            // The queue key is the prune height followed by the keypair key
+            let mut keypair_key = KEYPAIR_DATA_KEY.to_vec();
+            keypair_key.append(&mut queue_key[8..].to_vec());
+
+            let tlcs_store = ctx.get_kv_store(&self.store_key);
+            if let Some(raw) = tlcs_store.get(&keypair_key) {
+                let keypair = RawMsgKeyPair::decode::<Bytes>(raw.into())
+                    .expect("invalid data in database - possible database corruption");
+                let contributions_prefix =
+                    keypair_contributions_prefix(keypair.round, keypair.scheme, keypair.id);
+                let contributions: Vec<Vec<u8>> = tlcs_store
+                    .get_immutable_prefix_store(contributions_prefix.clone())
+                    .range(..)
+                    .map(|(key, _)| key)
+                    .collect();
+
+                let archived = RawArchivedKeyPair {
+                    round: keypair.round,
+                    scheme: keypair.scheme,
+                    id: keypair.id,
+                    public_key: keypair.public_key,
+                    private_key: keypair.private_key,
+                    archived_height: height,
+                    status: keypair.status,
+                };
+
+                info!(
+                    "PRUNE: archiving keypair. Round: {:?}, Scheme: {:?}, Id: {:?}",
+                    archived.round, archived.scheme, archived.id
+                );
+
+                let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
+                tlcs_store.delete(&keypair_key);
+                for mut key in contributions {
+                    let mut contribution_key = contributions_prefix.clone();
+                    contribution_key.append(&mut key);
+                    tlcs_store.delete(&contribution_key);
+                    stats.pruned_contributions += 1;
+                }
+                tlcs_store.set(
+                    archived_keypair_store_key(archived.round, archived.scheme, archived.id),
+                    archived.encode_to_vec(),
+                );
+                stats.archived_keypairs += 1;
+
+                // The LOE data is shared by all the keypairs of the beacon's round
+                let mut round_prefix = KEYPAIR_DATA_KEY.to_vec();
+                round_prefix.append(&mut archived.round.to_be_bytes().to_vec());
+                let round_in_use = ctx
+                    .get_kv_store(&self.store_key)
+                    .get_immutable_prefix_store(round_prefix)
+                    .range(..)
+                    .next()
+                    .is_some();
+
+                let loe_key = loe_data_store_key(keypair.beacon_id, archived.round);
+                let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
+                if !round_in_use && tlcs_store.get(&loe_key).is_some() {
+                    tlcs_store.delete(&loe_key);
+                    stats.pruned_loe_data += 1;
+                }
+            }
+
+            let mut store_key = RELEASED_QUEUE_KEY.to_vec();
+            store_key.append(&mut queue_key.clone());
+            let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
+            tlcs_store.delete(&store_key);
+        }
+
+        stats.last_pruned_height = height;
         let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
-        let contribution_threshold = tlcs_store.get(&CONTRIBUTION_THRESHOLD_KEY);
+        tlcs_store.set(PRUNE_STATS_KEY.to_vec(), stats.encode_to_vec());
+    }
 
-        match contribution_threshold {
-            None => 0, //initialize (initializing to zero means that round zero can never be processed!)
-            Some(num) => u32::decode::<Bytes>(num.to_owned().into())
+    fn get_prune_stats<T: Database>(&self, ctx: &TxContext<T, SK>) -> QueryPruneStatsResponse {
+        match ctx.get_kv_store(&self.store_key).get(&PRUNE_STATS_KEY) {
+            Some(raw) => QueryPruneStatsResponse::decode::<Bytes>(raw.into())
                 .expect("invalid data in database - possible database corruption"),
+            None => QueryPruneStatsResponse::default(),
         }
     }
 
-    #[allow(dead_code)]
-    pub fn set_contribution_threshold<T: Database>(
+    pub fn query_prune_stats<T: Database>(
         &self,
-        ctx: &mut TxContext<T, SK>,
-        new_threshold: u32,
-    ) -> Result<(), AppError> {
-        let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
-        let prefix = CONTRIBUTION_THRESHOLD_KEY.to_vec();
-        tlcs_store.set(prefix, new_threshold.encode_to_vec());
+        ctx: &QueryContext<T, SK>,
+    ) -> QueryPruneStatsResponse {
+        let tlcs_store = ctx.get_kv_store(&self.store_key);
 
-        Ok(())
+        let mut stats = match tlcs_store.get(&PRUNE_STATS_KEY) {
+            Some(raw) => QueryPruneStatsResponse::decode::<Bytes>(raw.into())
+                .expect("invalid data in database - possible database corruption"),
+            None => QueryPruneStatsResponse::default(),
+        };
+        stats.pending = tlcs_store
+            .get_immutable_prefix_store(RELEASED_QUEUE_KEY.to_vec())
+            .range(..)
+            .count() as u64;
+
+        stats
+    }
+
+    pub fn query_pending_work<T: Database>(
+        &self,
+        ctx: &QueryContext<T, SK>,
+    ) -> QueryPendingWorkResponse {
+        match ctx.get_kv_store(&self.store_key).get(&PENDING_WORK_KEY) {
+            Some(raw) => QueryPendingWorkResponse::decode::<Bytes>(raw.into())
+                .expect("invalid data in database - possible database corruption"),
+            None => QueryPendingWorkResponse::default(),
+        }
+    }
+
+    pub fn query_archived_keypairs<T: Database>(
+        &self,
+        ctx: &QueryContext<T, SK>,
+        pagination: Option<PageRequest>,
+    ) -> QueryArchivedKeyPairsResponse {
+        let tlcs_store = ctx.get_kv_store(&self.store_key);
+        let prefix_store = tlcs_store.get_immutable_prefix_store(ARCHIVED_KEYPAIR_KEY.to_vec());
+
+        let (rows, page) = paginate(&prefix_store, vec![], None, pagination);
+
+        let mut keypairs = vec![];
+        for (_, row) in rows {
+            let keypair: RawArchivedKeyPair = RawArchivedKeyPair::decode::<Bytes>(row.into())
+                .expect("invalid data in database - possible database corruption");
+            keypairs.push(keypair);
+        }
+
+        QueryArchivedKeyPairsResponse {
+            keypairs,
+            pagination: Some(page),
+        }
     }
 
-    pub fn get_this_round_all_participant_data<T: Database>(
+    pub fn get_keypair_participant_data<T: Database>(
         &self,
         ctx: &mut TxContext<T, SK>,
         round: u64,
         scheme: u32,
+        id: u32,
     ) -> Vec<(Vec<u8>, Vec<u8>)> {
-        let mut prefix = PARTICIPANT_DATA_KEY.to_vec();
-        prefix.append(&mut round.to_le_bytes().to_vec());
-        prefix.append(&mut scheme.to_le_bytes().to_vec());
-
         let tlcs_store = ctx.get_kv_store(&self.store_key);
         tlcs_store
-            .get_immutable_prefix_store(prefix)
+            .get_immutable_prefix_store(keypair_contributions_prefix(round, scheme, id))
             .range(..)
             .collect()
     }
@@ -804,14 +2878,13 @@ impl<SK: StoreKey> Keeper<SK> {
     pub fn get_this_round_loe_signature<T: Database>(
         &self,
         ctx: &mut TxContext<T, SK>,
+        beacon_id: u32,
         round: u64,
         //) -> Option<RawMsgLoeData> {
     ) -> Option<String> {
         let tlcs_store = ctx.get_kv_store(&self.store_key);
 
-        let mut prefix = LOE_DATA_KEY.to_vec();
-        prefix.append(&mut round.to_le_bytes().to_vec());
-        let store_data = tlcs_store.get(&prefix);
+        let store_data = tlcs_store.get(&loe_data_store_key(beacon_id, round));
 
         match store_data {
             Some(store_data) => {
@@ -823,28 +2896,46 @@ impl<SK: StoreKey> Keeper<SK> {
         }
     }
 
+    /// Keypairs waiting for LOE data, with the beacons the data must come from
     pub fn query_loe_data_needed<T: Database>(
         &self,
         ctx: &QueryContext<T, SK>,
-    ) -> QueryAllKeyPairsResponse {
+        pagination: Option<PageRequest>,
+    ) -> QueryLoeDataNeededResponse {
         let tlcs_store = ctx.get_kv_store(&self.store_key);
-        let store_key = KEYPAIR_DATA_KEY.to_vec();
-
-        let prefix_store = tlcs_store.get_immutable_prefix_store(store_key);
-        let all_raw_data = prefix_store.range(..);
+        let prefix_store = tlcs_store.get_immutable_prefix_store(LOE_QUEUE_KEY.to_vec());
+        let (queue, page) = paginate(&prefix_store, vec![], None, pagination);
 
         let mut keypairs = vec![];
+        let mut beacons: Vec<RawBeacon> = vec![];
 
-        for (_, row) in all_raw_data {
+        // Currently, can't get blocktime in here so the time filtering is done in the loe watcher
+        for (queue_key, _) in queue {
+            // The queue key is the beacon id followed by the keypair key
+            let mut store_key = KEYPAIR_DATA_KEY.to_vec();
+            store_key.append(&mut queue_key[4..].to_vec());
+            let row = tlcs_store
+                .get(&store_key)
+                .expect("invalid data in database - possible database corruption");
             let keypair: RawMsgKeyPair = RawMsgKeyPair::decode::<Bytes>(row.into())
                 .expect("invalid data in database - possible database corruption");
-            //TODO: Possibly also filter by blocktime. It would be better but for now we'll just get records with empty private keys
-            // Currently, can't get blocktime in here so the time filtering is done in the loe watcher
-            if !keypair.public_key.is_empty() && keypair.private_key.is_empty() {
-                keypairs.push(keypair);
+
+            if !beacons.iter().any(|beacon| beacon.id == keypair.beacon_id) {
+                let row = tlcs_store
+                    .get(&beacon_store_key(keypair.beacon_id))
+                    .expect("invalid data in database - possible database corruption");
+                let beacon: RawBeacon = RawBeacon::decode::<Bytes>(row.into())
+                    .expect("invalid data in database - possible database corruption");
+                beacons.push(beacon);
             }
+
+            keypairs.push(keypair);
+        }
+        QueryLoeDataNeededResponse {
+            keypairs,
+            beacons,
+            pagination: Some(page),
         }
-        QueryAllKeyPairsResponse { keypairs }
     }
 
     #[allow(dead_code)]
@@ -857,9 +2948,12 @@ impl<SK: StoreKey> Keeper<SK> {
         let tlcs_store = ctx.get_mutable_kv_store(&self.store_key);
 
         let mut prefix = KEYPAIR_DATA_KEY.to_vec();
-        prefix.append(&mut round.to_le_bytes().to_vec());
-        prefix.append(&mut scheme.to_le_bytes().to_vec());
+        prefix.append(&mut round.to_be_bytes().to_vec());
+        prefix.append(&mut scheme.to_be_bytes().to_vec());
 
         tlcs_store.get_mutable_prefix_store(prefix)
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,2064 @@
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use database::MemDB;
+use gears::x::params::{Keeper as ParamsKeeper, ParamsSubspaceKey};
+use proto_messages::cosmos::bank::v1beta1::QueryBalanceRequest;
+use store::{MultiStore, StoreKey};
+use strum_macros::EnumIter;
+use tendermint_informal::{
+    account,
+    block::{header::Version, Header, Height},
+    chain, AppHash, Hash, Time,
+};
+use tendermint_proto::abci::Event;
+use tendermint_proto::abci::RequestBeginBlock;
+use tendermint_rpc::Url;
+
+use super::*;
+use crate::{Handler, Message as TimelockMessage, DRAND_DEFAULT_BEACON_ID};
+
+#[derive(EnumIter, Debug, PartialEq, Eq, Hash, Clone)]
+enum TestStoreKey {
+    Bank,
+    Auth,
+    Params,
+    Timelock,
+}
+
+impl StoreKey for TestStoreKey {
+    fn name(&self) -> &'static str {
+        match self {
+            TestStoreKey::Bank => "bank",
+            TestStoreKey::Auth => "acc",
+            TestStoreKey::Params => "params",
+            TestStoreKey::Timelock => "timelock",
+        }
+    }
+}
+
+#[derive(EnumIter, Debug, PartialEq, Eq, Hash, Clone)]
+enum TestParamsStoreKey {
+    Bank,
+    Auth,
+    Timelock,
+}
+
+impl ParamsSubspaceKey for TestParamsStoreKey {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Bank => "bank/",
+            Self::Auth => "auth/",
+            Self::Timelock => "timelock/",
+        }
+    }
+}
+
+const CHAIN_ID: &str = "test-chain";
+const GENESIS_TIME: i64 = 1_700_000_000;
+const BLOCK_PERIOD: i64 = 6;
+
+const REQUESTER: &str = "cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux";
+const CONTRIBUTORS: [&str; 3] = [
+    "cosmos1skgmlw2j4qupafzcg5qvacd76mfzfe69la0hxz",
+    "cosmos1gc308w6mg7skucsdxdjehhewr4aetwq24zf92m",
+    "cosmos180tr8wmsk8ugt32yynj8efqwg3yglmpwp22rut",
+];
+
+/// Params without bonds or fees, so the tests don't need bank balances
+fn test_params() -> Params {
+    Params {
+        contributor_bond: 0,
+        request_fee: 0,
+        min_contribution_threshold: 1,
+        ..Params::default()
+    }
+}
+
+fn test_genesis() -> GenesisState {
+    GenesisState {
+        params: Some(test_params().into()),
+        contributors: CONTRIBUTORS
+            .iter()
+            .map(|address| RawContributor {
+                address: address.to_string(),
+                bond_denom: test_params().bond_denom,
+                ..RawContributor::default()
+            })
+            .collect(),
+        ..GenesisState::default()
+    }
+}
+
+fn block_time(height: u64) -> i64 {
+    GENESIS_TIME + height as i64 * BLOCK_PERIOD
+}
+
+/// The first height whose block time is past `time`
+fn height_after(time: i64) -> u64 {
+    (time - GENESIS_TIME) as u64 / BLOCK_PERIOD as u64 + 1
+}
+
+fn header(height: u64) -> Header {
+    Header {
+        version: Version { block: 11, app: 0 },
+        chain_id: chain::Id::try_from(CHAIN_ID).unwrap(),
+        height: Height::try_from(height).unwrap(),
+        time: Time::from_unix_timestamp(block_time(height), 0).unwrap(),
+        last_block_id: None,
+        last_commit_hash: None,
+        data_hash: None,
+        validators_hash: Hash::None,
+        next_validators_hash: Hash::None,
+        consensus_hash: Hash::None,
+        app_hash: AppHash::default(),
+        last_results_hash: None,
+        evidence_hash: None,
+        proposer_address: account::Id::new([0; 20]),
+    }
+}
+
+/// An app instance with its own memory store. The keyshares of the begin blocker are sent from
+/// the node's key store, the instances have none so they only run the state transitions.
+struct TestApp {
+    store: MultiStore<MemDB, TestStoreKey>,
+    handler: Handler<TestStoreKey, TestParamsStoreKey>,
+    keeper: Keeper<TestStoreKey, TestParamsStoreKey>,
+    auth_keeper: auth::Keeper<TestStoreKey, TestParamsStoreKey>,
+    /// Version of the last commit, which the queries read
+    version: u32,
+    /// Events of the begin blocker and the accepted txs of the last block
+    events: Vec<Event>,
+}
+
+impl TestApp {
+    /// An instance whose store hasn't been through init genesis
+    fn empty() -> Self {
+        let params_keeper = ParamsKeeper::new(TestStoreKey::Params);
+        let auth_keeper = auth::Keeper::new(
+            TestStoreKey::Auth,
+            params_keeper.clone(),
+            TestParamsStoreKey::Auth,
+        );
+        let bank_keeper = bank::Keeper::new(
+            TestStoreKey::Bank,
+            params_keeper.clone(),
+            TestParamsStoreKey::Bank,
+            auth_keeper.clone(),
+        );
+        let keeper = Keeper::new(
+            TestStoreKey::Timelock,
+            params_keeper,
+            TestStoreKey::Params,
+            TestParamsStoreKey::Timelock,
+            bank_keeper,
+        );
+        let config = Config {
+            node: Url::from_str("http://localhost:26657").unwrap(),
+            home: std::env::temp_dir().join("tlcs-keeper-tests"),
+            from: "test".into(),
+            chain_id: chain::Id::try_from(CHAIN_ID).unwrap(),
+            delay: 0,
+        };
+
+        TestApp {
+            store: MultiStore::new(MemDB::new()),
+            handler: Handler::new(keeper.clone(), config),
+            keeper,
+            auth_keeper,
+            version: 0,
+            events: vec![],
+        }
+    }
+
+    fn new(genesis: GenesisState) -> Self {
+        TestApp::with_balances(genesis, &[])
+    }
+
+    /// An instance whose accounts start with the given amounts of the bond denom
+    fn with_balances(genesis: GenesisState, balances: &[(&str, u64)]) -> Self {
+        let mut app = TestApp::empty();
+        let auth_handler = auth::Handler::new(app.auth_keeper.clone());
+        let bank_handler = bank::Handler::new(app.keeper.bank_keeper.clone());
+
+        let mut auth_genesis = auth::GenesisState {
+            accounts: vec![],
+            params: gears::x::auth::Params {
+                max_memo_characters: 256,
+                tx_sig_limit: 7,
+                tx_size_cost_per_byte: 10,
+                sig_verify_cost_ed25519: 590,
+                sig_verify_cost_secp256k1: 1000,
+            },
+        };
+        let mut bank_genesis = bank::GenesisState {
+            balances: vec![],
+            params: bank::Params {
+                default_send_enabled: true,
+            },
+        };
+        for (address, amount) in balances {
+            let address = AccAddress::from_bech32(address).unwrap();
+            auth_handler
+                .handle_add_genesis_account(&mut auth_genesis, address.clone())
+                .unwrap();
+            bank_handler.handle_add_genesis_account(
+                &mut bank_genesis,
+                address,
+                bond_coins(*amount),
+            );
+        }
+
+        let mut ctx = InitContext::new(&mut app.store, 0, CHAIN_ID.into());
+        bank_handler.init_genesis(&mut ctx, bank_genesis);
+        auth_handler.init_genesis(&mut ctx, auth_genesis);
+        app.handler.init_genesis(&mut ctx, genesis);
+        app.commit();
+
+        app
+    }
+
+    fn commit(&mut self) -> [u8; 32] {
+        self.store.write_then_clear_tx_caches();
+        self.version += 1;
+        self.store.commit()
+    }
+
+    /// Runs the begin blocker and the txs of a block, then commits it. Like the base app, each
+    /// tx writes to the tx cache, which is only written to the block when the tx succeeds.
+    /// Returns whether each tx was accepted and the app hash.
+    fn block(&mut self, height: u64, txs: &[TimelockMessage]) -> (Vec<bool>, [u8; 32]) {
+        let mut ctx = TxContext::new(&mut self.store, height, header(height), vec![]);
+        self.handler
+            .handle_begin_block(&mut ctx, RequestBeginBlock::default());
+        self.events = ctx.events;
+        self.store.write_then_clear_tx_caches();
+
+        let results = txs
+            .iter()
+            .map(|tx| self.deliver_tx(height, tx).is_ok())
+            .collect();
+
+        (results, self.commit())
+    }
+
+    fn deliver_tx(&mut self, height: u64, tx: &TimelockMessage) -> Result<(), AppError> {
+        let mut ctx = TxContext::new(&mut self.store, height, header(height), vec![]);
+        let result = self.handler.handle(&mut ctx, tx);
+
+        match result {
+            Ok(_) => {
+                self.events.append(&mut ctx.events);
+                self.store.write_then_clear_tx_caches();
+            }
+            Err(_) => self.store.clear_tx_caches(),
+        }
+
+        result
+    }
+
+    /// Runs a query against the last commit
+    fn query<R>(
+        &self,
+        query: impl FnOnce(
+            &Keeper<TestStoreKey, TestParamsStoreKey>,
+            &QueryContext<MemDB, TestStoreKey>,
+        ) -> R,
+    ) -> R {
+        let ctx = QueryContext::new(&self.store, self.version).unwrap();
+        query(&self.keeper, &ctx)
+    }
+
+    fn balance(&self, address: &str) -> u64 {
+        let response = self.query(|keeper, ctx| {
+            keeper.bank_keeper.query_balance(
+                ctx,
+                QueryBalanceRequest {
+                    address: AccAddress::from_bech32(address).unwrap(),
+                    denom: Denom::try_from(test_params().bond_denom).unwrap(),
+                },
+            )
+        });
+
+        response
+            .balance
+            .map_or(0, |coin| coin.amount.to_string().parse().unwrap())
+    }
+
+    fn get(&mut self, store_key: &[u8]) -> Option<Vec<u8>> {
+        let ctx = TxContext::new(&mut self.store, 0, header(0), vec![]);
+        ctx.get_kv_store(&TestStoreKey::Timelock).get(store_key)
+    }
+
+    /// An instance whose store holds the rows the first release of the module wrote. It had no
+    /// params and no store version.
+    fn first_release(rows: &[(Vec<u8>, Vec<u8>)]) -> Self {
+        let mut app = TestApp::empty();
+        app.write(rows);
+
+        app
+    }
+
+    /// Writes rows to the store directly, bypassing the checks of the module
+    fn write(&mut self, rows: &[(Vec<u8>, Vec<u8>)]) {
+        let mut ctx = TxContext::new(&mut self.store, 0, header(0), vec![]);
+        for (store_key, row) in rows {
+            ctx.get_mutable_kv_store(&TestStoreKey::Timelock)
+                .set(store_key.clone(), row.clone());
+        }
+        self.commit();
+    }
+
+    fn contribution_threshold(&mut self) -> u32 {
+        let mut ctx = TxContext::new(&mut self.store, 0, header(0), vec![]);
+        self.keeper.contribution_threshold(&mut ctx)
+    }
+
+    /// Ids of the keypairs the begin blocker of the height would make public and private keys for
+    fn empty_keypairs(&mut self, height: u64) -> (Vec<u32>, Vec<u32>) {
+        let mut ctx = TxContext::new(&mut self.store, height, header(height), vec![]);
+        let (public, private) = self.keeper.get_empty_keypairs(&mut ctx, block_time(height));
+        self.commit();
+
+        (
+            public.into_iter().map(|(_, keypair)| keypair.id).collect(),
+            private.into_iter().map(|(_, keypair)| keypair.id).collect(),
+        )
+    }
+
+    /// The shares the keys of the keypair are made from
+    fn shares(&mut self, round: u64, id: u32) -> Vec<Vec<u8>> {
+        let mut ctx = TxContext::new(&mut self.store, 0, header(0), vec![]);
+
+        self.keeper
+            .get_keypair_participant_data(&mut ctx, round, 1, id)
+            .into_iter()
+            .map(|(_, raw)| {
+                RawMsgContribution::decode::<Bytes>(raw.into())
+                    .unwrap()
+                    .data
+            })
+            .collect()
+    }
+
+    fn keypair(&mut self, round: u64, id: u32) -> Option<RawMsgKeyPair> {
+        let ctx = TxContext::new(&mut self.store, 0, header(0), vec![]);
+
+        ctx.get_kv_store(&TestStoreKey::Timelock)
+            .get(&keypair_store_key(round, 1, id))
+            .map(|raw| RawMsgKeyPair::decode::<Bytes>(raw.into()).unwrap())
+    }
+
+    fn contribution(&mut self, round: u64, id: u32, address: &str) -> Option<RawMsgContribution> {
+        let ctx = TxContext::new(&mut self.store, 0, header(0), vec![]);
+        let store_key =
+            contribution_store_key(round, 1, id, AccAddress::from_bech32(address).unwrap());
+
+        ctx.get_kv_store(&TestStoreKey::Timelock)
+            .get(&store_key)
+            .map(|raw| RawMsgContribution::decode::<Bytes>(raw.into()).unwrap())
+    }
+}
+
+/// The types of the events of the last block, in the order they were emitted
+fn event_kinds(events: &[Event]) -> Vec<String> {
+    events.iter().map(|event| event.r#type.clone()).collect()
+}
+
+fn event_attribute(event: &Event, key: &str) -> String {
+    let attribute = event
+        .attributes
+        .iter()
+        .find(|attribute| attribute.key == key)
+        .unwrap();
+    String::from_utf8(attribute.value.to_vec()).unwrap()
+}
+
+fn cancel(round: u64, id: u32) -> TimelockMessage {
+    TimelockMessage::CancelKeyRequest(MsgCancelKeyRequest {
+        address: AccAddress::from_bech32(REQUESTER).unwrap(),
+        round,
+        scheme: 1,
+        id,
+    })
+}
+
+fn bond_coins(amount: u64) -> SendCoins {
+    SendCoins::new(vec![Coin {
+        denom: Denom::try_from(test_params().bond_denom).unwrap(),
+        amount: amount.into(),
+    }])
+    .unwrap()
+}
+
+fn new_process(round: u64, pubkey_time: i64) -> TimelockMessage {
+    TimelockMessage::NewProcess(MsgNewProcess {
+        address: AccAddress::from_bech32(REQUESTER).unwrap(),
+        round,
+        scheme: 1,
+        pubkey_time,
+        canonical: false,
+        beacon_id: QUICKNET_BEACON_ID,
+    })
+}
+
+fn contribute(contributor: &str, round: u64, id: u32) -> TimelockMessage {
+    TimelockMessage::Participate(MsgContribution {
+        address: AccAddress::from_bech32(contributor).unwrap(),
+        round,
+        scheme: 1,
+        id,
+        data: make_keyshare(
+            default_beacons()[0].public_key.clone(),
+            round,
+            scheme_to_string(1),
+            test_params().security_param as usize,
+        ),
+    })
+}
+
+#[test]
+fn key_requests_are_checked_against_the_block_time() {
+    let lead_time = test_params().min_pubkey_lead_time as i64;
+    let time = block_time(1);
+    let txs = vec![
+        new_process(1000, time + lead_time),
+        new_process(1000, time + lead_time - 1),
+        new_process(1000, time - 1),
+        TimelockMessage::MultiNewProcess(MsgMultiNewProcess {
+            address: AccAddress::from_bech32(REQUESTER).unwrap(),
+            startround: 1000,
+            reqnum: 2,
+            roundstep: 10,
+            schemes: vec![1],
+            pubkey_time: time + lead_time,
+            beacon_id: QUICKNET_BEACON_ID,
+        }),
+    ];
+
+    let mut first = TestApp::new(test_genesis());
+    let mut second = TestApp::new(test_genesis());
+
+    let first_block = first.block(1, &txs);
+    let second_block = second.block(1, &txs);
+
+    assert_eq!(first_block.0, vec![true, false, false, true]);
+    assert_eq!(first_block, second_block);
+}
+
+#[test]
+fn contribution_threshold_is_two_thirds_plus_one_with_a_floor() {
+    for (active, floor, expected) in [(1, 1, 1), (2, 1, 2), (3, 1, 3), (1, 2, 2), (3, 4, 4)] {
+        let mut genesis = test_genesis();
+        genesis.contributors.truncate(active);
+        genesis.params = Some(
+            Params {
+                min_contribution_threshold: floor,
+                ..test_params()
+            }
+            .into(),
+        );
+        let mut app = TestApp::new(genesis);
+
+        assert_eq!(
+            app.contribution_threshold(),
+            expected,
+            "{} active contributors, floor {}",
+            active,
+            floor
+        );
+    }
+}
+
+#[test]
+fn keypairs_fail_when_the_floor_is_above_the_active_contributors() {
+    let mut genesis = test_genesis();
+    genesis.contributors.truncate(1);
+    genesis.params = Some(
+        Params {
+            min_contribution_threshold: 2,
+            ..test_params()
+        }
+        .into(),
+    );
+    let mut app = TestApp::new(genesis);
+
+    let pubkey_time = block_time(1) + test_params().min_pubkey_lead_time as i64;
+    app.block(1, &[new_process(1000, pubkey_time)]);
+    let (results, _) = app.block(2, &[contribute(CONTRIBUTORS[0], 1000, 1)]);
+    assert_eq!(results, vec![true]);
+    app.block(height_after(pubkey_time), &[]);
+
+    let keypair = app.keypair(1000, 1).unwrap();
+    assert_eq!(keypair.status(), KeyPairStatus::Failed);
+    assert!(keypair.public_key.is_empty());
+}
+
+#[test]
+fn keypairs_in_an_inconsistent_status_fail_without_halting() {
+    let mut app = TestApp::new(test_genesis());
+
+    // A requested keypair with shares, which can't move to public key ready
+    let keypair = RawMsgKeyPair {
+        round: 1000,
+        scheme: 1,
+        id: 1,
+        pubkey_time: block_time(0),
+        ..Default::default()
+    };
+    let mut rows = vec![
+        (keypair_store_key(1000, 1, 1), keypair.encode_to_vec()),
+        (
+            pubkey_queue_store_key(keypair.pubkey_time, 1000, 1, 1),
+            vec![1],
+        ),
+    ];
+    for contributor in CONTRIBUTORS {
+        let contribution = RawMsgContribution {
+            address: contributor.into(),
+            round: 1000,
+            scheme: 1,
+            id: 1,
+            ..Default::default()
+        };
+        rows.push((
+            contribution_store_key(1000, 1, 1, AccAddress::from_bech32(contributor).unwrap()),
+            contribution.encode_to_vec(),
+        ));
+    }
+    app.write(&rows);
+
+    app.block(1, &[]);
+
+    let keypair = app.keypair(1000, 1).unwrap();
+    assert_eq!(keypair.status(), KeyPairStatus::Failed);
+    assert!(keypair.public_key.is_empty());
+    assert!(app
+        .get(&pubkey_queue_store_key(keypair.pubkey_time, 1000, 1, 1))
+        .is_none());
+}
+
+#[test]
+fn keys_are_only_requested_for_beacons_they_can_be_timelocked_to() {
+    let mut app = TestApp::new(test_genesis());
+    let pubkey_time = block_time(1) + test_params().min_pubkey_lead_time as i64;
+
+    let mut drand_default = new_process(1000, pubkey_time);
+    if let TimelockMessage::NewProcess(msg) = &mut drand_default {
+        msg.beacon_id = DRAND_DEFAULT_BEACON_ID;
+    }
+    let (results, _) = app.block(1, &[drand_default, new_process(1000, pubkey_time)]);
+
+    assert_eq!(results, vec![false, true]);
+    assert_eq!(app.keypair(1000, 1).unwrap().beacon_id, QUICKNET_BEACON_ID);
+    assert!(app.keypair(1000, 2).is_none());
+}
+
+#[test]
+fn keys_are_not_requested_for_retired_beacons() {
+    let mut genesis = test_genesis();
+    genesis.authority = REQUESTER.into();
+    let mut app = TestApp::new(genesis);
+    let update_quicknet = |retired: bool| {
+        TimelockMessage::UpdateBeacon(MsgUpdateBeacon {
+            authority: AccAddress::from_bech32(REQUESTER).unwrap(),
+            beacon: RawBeacon {
+                retired,
+                ..default_beacons()[0].clone()
+            },
+        })
+    };
+
+    let pubkey_time = block_time(1) + test_params().min_pubkey_lead_time as i64;
+    let (results, _) = app.block(
+        1,
+        &[
+            new_process(1000, pubkey_time),
+            update_quicknet(true),
+            new_process(1000, pubkey_time),
+        ],
+    );
+    assert_eq!(results, vec![true, true, false]);
+    assert!(app.keypair(1000, 2).is_none());
+
+    // The keypairs requested before the beacon was retired are still served
+    let (results, _) = app.block(2, &[contribute(CONTRIBUTORS[0], 1000, 1)]);
+    assert_eq!(results, vec![true]);
+
+    let (results, _) = app.block(3, &[update_quicknet(false), new_process(1000, pubkey_time)]);
+    assert_eq!(results, vec![true, true]);
+    assert_eq!(app.keypair(1000, 2).unwrap().beacon_id, QUICKNET_BEACON_ID);
+}
+
+#[test]
+fn loe_data_is_stored_per_beacon() {
+    let loe_data = |beacon_id: u32, signature: &str| RawMsgLoeData {
+        round: 1000,
+        signature: signature.into(),
+        beacon_id,
+        ..RawMsgLoeData::default()
+    };
+    let mut genesis = test_genesis();
+    genesis.loe_data = vec![
+        loe_data(QUICKNET_BEACON_ID, "quicknet"),
+        loe_data(DRAND_DEFAULT_BEACON_ID, "default"),
+    ];
+    let mut app = TestApp::new(genesis);
+
+    let mut ctx = TxContext::new(&mut app.store, 0, header(0), vec![]);
+    assert_eq!(
+        app.keeper
+            .get_this_round_loe_signature(&mut ctx, QUICKNET_BEACON_ID, 1000),
+        Some("quicknet".into())
+    );
+    assert_eq!(
+        app.keeper
+            .get_this_round_loe_signature(&mut ctx, DRAND_DEFAULT_BEACON_ID, 1000),
+        Some("default".into())
+    );
+    assert_eq!(
+        app.keeper
+            .get_this_round_loe_signature(&mut ctx, QUICKNET_BEACON_ID, 1001),
+        None
+    );
+
+    let by_round = app.query(|keeper, ctx| keeper.query_loe_data_by_round(ctx, 1000, None));
+    let stored: Vec<(u32, String)> = by_round
+        .randomnesses
+        .into_iter()
+        .map(|loe_data| (loe_data.beacon_id, loe_data.signature))
+        .collect();
+    assert_eq!(
+        stored,
+        vec![
+            (QUICKNET_BEACON_ID, "quicknet".to_string()),
+            (DRAND_DEFAULT_BEACON_ID, "default".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn failed_txs_leave_no_writes() {
+    let mut genesis = test_genesis();
+    let mut params = test_params();
+    params.request_fee = 10;
+    genesis.params = Some(params.into());
+    // Enough for the fee of the first keypair of the multi request only
+    let mut app = TestApp::with_balances(genesis, &[(REQUESTER, 15)]);
+
+    let pubkey_time = block_time(1) + test_params().min_pubkey_lead_time as i64;
+    let (results, _) = app.block(
+        1,
+        &[TimelockMessage::MultiNewProcess(MsgMultiNewProcess {
+            address: AccAddress::from_bech32(REQUESTER).unwrap(),
+            startround: 1000,
+            reqnum: 2,
+            roundstep: 10,
+            schemes: vec![1],
+            pubkey_time,
+            beacon_id: QUICKNET_BEACON_ID,
+        })],
+    );
+
+    assert_eq!(results, vec![false]);
+    assert!(app.keypair(1000, 1).is_none());
+    assert_eq!(app.balance(REQUESTER), 15);
+    assert!(app.events.is_empty());
+}
+
+#[test]
+fn request_fees_are_escrowed_then_paid_out_or_refunded() {
+    let mut genesis = test_genesis();
+    genesis.params = Some(
+        Params {
+            request_fee: 10,
+            ..test_params()
+        }
+        .into(),
+    );
+    // The contributors get a coin so their accounts exist before the payout
+    let mut app = TestApp::with_balances(
+        genesis,
+        &[
+            (REQUESTER, 30),
+            (CONTRIBUTORS[0], 1),
+            (CONTRIBUTORS[1], 1),
+            (CONTRIBUTORS[2], 1),
+        ],
+    );
+
+    let pubkey_time = block_time(1) + test_params().min_pubkey_lead_time as i64;
+    let requests = vec![new_process(1000, pubkey_time); 2];
+    assert_eq!(app.block(1, &requests).0, vec![true, true]);
+
+    // The fees are held by the module account until the public key is due
+    assert_eq!(app.balance(REQUESTER), 10);
+    assert_eq!(app.balance(TIMELOCK_MODULE_ADDRESS), 20);
+    let escrows = app.query(|keeper, ctx| keeper.query_fee_escrows(ctx));
+    assert_eq!(
+        escrows
+            .iter()
+            .map(|escrow| (escrow.id, escrow.payer.as_str(), escrow.amount))
+            .collect::<Vec<_>>(),
+        vec![(1, REQUESTER, 10), (2, REQUESTER, 10)]
+    );
+
+    let shares: Vec<TimelockMessage> = CONTRIBUTORS
+        .iter()
+        .map(|contributor| contribute(contributor, 1000, 1))
+        .collect();
+    assert_eq!(app.block(2, &shares).0, vec![true; 3]);
+
+    // The made keypair pays its contributors, the first address in order gets the remainder.
+    // The keypair without contributions fails and its fee goes back to the requester.
+    app.block(height_after(pubkey_time), &[]);
+    assert_eq!(
+        app.keypair(1000, 2).unwrap().status(),
+        KeyPairStatus::Failed
+    );
+    assert_eq!(app.balance(CONTRIBUTORS[2]), 1 + 4);
+    assert_eq!(app.balance(CONTRIBUTORS[1]), 1 + 3);
+    assert_eq!(app.balance(CONTRIBUTORS[0]), 1 + 3);
+    assert_eq!(app.balance(REQUESTER), 20);
+    assert_eq!(app.balance(TIMELOCK_MODULE_ADDRESS), 0);
+    assert!(app
+        .query(|keeper, ctx| keeper.query_fee_escrows(ctx))
+        .is_empty());
+}
+
+/// Builds a key the way the first release did, with little endian fields
+fn first_release_key(prefix: [u8; 1], round: u64, fields: &[u32]) -> Vec<u8> {
+    let mut store_key = prefix.to_vec();
+    store_key.append(&mut round.to_le_bytes().to_vec());
+    for field in fields {
+        store_key.append(&mut field.to_le_bytes().to_vec());
+    }
+    store_key
+}
+
+#[test]
+fn first_release_store_is_migrated() {
+    let pubkey_time = block_time(1) + 1000;
+    let released = RawMsgKeyPair {
+        round: 1000,
+        scheme: 1,
+        id: 1,
+        pubkey_time: block_time(0),
+        public_key: "public".into(),
+        private_key: "private".into(),
+        ..Default::default()
+    };
+    // A multi request, keyed without its id, and a single request which got the same id
+    let multi = RawMsgKeyPair {
+        round: 1010,
+        scheme: 1,
+        id: 1,
+        pubkey_time,
+        ..Default::default()
+    };
+    let single = RawMsgKeyPair {
+        pubkey_time: pubkey_time + 6,
+        ..multi.clone()
+    };
+    let contribution = RawMsgContribution {
+        address: CONTRIBUTORS[0].into(),
+        round: 1010,
+        scheme: 1,
+        id: 1,
+        data: vec![1, 2, 3],
+        ..Default::default()
+    };
+    let loe_data = RawMsgLoeData {
+        address: CONTRIBUTORS[0].into(),
+        round: 1000,
+        signature: "signature".into(),
+        ..Default::default()
+    };
+
+    let mut contribution_key = first_release_key(PARTICIPANT_DATA_KEY, 1010, &[1]);
+    let address: Vec<u8> = AccAddress::from_bech32(CONTRIBUTORS[0]).unwrap().into();
+    contribution_key.append(&mut address.to_vec());
+    let old_rows = vec![
+        (
+            LEGACY_CONTRIBUTION_THRESHOLD_KEY.to_vec(),
+            2u32.encode_to_vec(),
+        ),
+        (
+            first_release_key(KEYPAIR_DATA_KEY, 1000, &[1, 1]),
+            released.encode_to_vec(),
+        ),
+        (
+            first_release_key(KEYPAIR_DATA_KEY, 1010, &[1]),
+            multi.encode_to_vec(),
+        ),
+        (
+            first_release_key(KEYPAIR_DATA_KEY, 1010, &[1, 1]),
+            single.encode_to_vec(),
+        ),
+        (contribution_key, contribution.encode_to_vec()),
+        (
+            first_release_key(LOE_DATA_KEY, 1000, &[]),
+            loe_data.encode_to_vec(),
+        ),
+    ];
+
+    let mut app = TestApp::first_release(&old_rows);
+    app.block(1, &[]);
+
+    for (store_key, _) in &old_rows {
+        assert_eq!(app.get(store_key), None);
+    }
+    assert_eq!(
+        app.get(&STORE_VERSION_KEY),
+        Some(STORE_VERSION.to_be_bytes().to_vec())
+    );
+    assert!(app.get(&beacon_store_key(QUICKNET_BEACON_ID)).is_some());
+
+    let keypair = app.keypair(1000, 1).unwrap();
+    assert_eq!(keypair.status(), KeyPairStatus::Released);
+    assert_eq!(keypair.private_key, released.private_key);
+
+    // The multi request comes first in key order so it keeps the id
+    let keypair = app.keypair(1010, 1).unwrap();
+    assert_eq!(keypair.status(), KeyPairStatus::Collecting);
+    assert_eq!(keypair.pubkey_time, multi.pubkey_time);
+    let keypair = app.keypair(1010, 2).unwrap();
+    assert_eq!(keypair.status(), KeyPairStatus::Collecting);
+    assert_eq!(keypair.pubkey_time, single.pubkey_time);
+    assert!(app
+        .get(&pubkey_queue_store_key(multi.pubkey_time, 1010, 1, 1))
+        .is_some());
+    assert!(app
+        .get(&pubkey_queue_store_key(single.pubkey_time, 1010, 1, 2))
+        .is_some());
+
+    for id in [1, 2] {
+        let migrated = app.contribution(1010, id, CONTRIBUTORS[0]).unwrap();
+        assert_eq!(migrated.id, id);
+        assert_eq!(migrated.data, contribution.data);
+    }
+    let migrated_loe_data = app
+        .get(&loe_data_store_key(QUICKNET_BEACON_ID, 1000))
+        .map(|raw| RawMsgLoeData::decode::<Bytes>(raw.into()).unwrap())
+        .unwrap();
+    assert_eq!(migrated_loe_data.signature, loe_data.signature);
+}
+
+#[test]
+fn migrated_keys_are_made_from_the_shares_of_their_round() {
+    let params = Params::default();
+    let shares: Vec<Vec<u8>> = CONTRIBUTORS
+        .iter()
+        .map(|_| {
+            make_keyshare(
+                default_beacons()[0].public_key.clone(),
+                1000,
+                scheme_to_string(1),
+                params.security_param as usize,
+            )
+        })
+        .collect();
+    let public_key = make_public_key(scheme_to_string(1), &shares);
+
+    // The first release made both public keys from all the shares of the round, whichever id
+    // the contributors named
+    let mut old_rows = vec![];
+    for id in [1, 2] {
+        let keypair = RawMsgKeyPair {
+            round: 1000,
+            scheme: 1,
+            id,
+            pubkey_time: block_time(0),
+            public_key: public_key.clone(),
+            ..Default::default()
+        };
+        old_rows.push((
+            first_release_key(KEYPAIR_DATA_KEY, 1000, &[1, id]),
+            keypair.encode_to_vec(),
+        ));
+    }
+    for (i, (contributor, data)) in CONTRIBUTORS.iter().zip(&shares).enumerate() {
+        let contribution = RawMsgContribution {
+            address: contributor.to_string(),
+            round: 1000,
+            scheme: 1,
+            id: if i == 0 { 1 } else { 2 },
+            data: data.clone(),
+            ..Default::default()
+        };
+        let mut store_key = first_release_key(PARTICIPANT_DATA_KEY, 1000, &[1]);
+        let address: Vec<u8> = AccAddress::from_bech32(contributor).unwrap().into();
+        store_key.append(&mut address.to_vec());
+        old_rows.push((store_key, contribution.encode_to_vec()));
+    }
+
+    let mut app = TestApp::first_release(&old_rows);
+    app.block(1, &[]);
+
+    // The secret key is made from the shares stored for the keypair, so they must rebuild the
+    // public key which was already published
+    for id in [1, 2] {
+        let keypair = app.keypair(1000, id).unwrap();
+        assert_eq!(keypair.status(), KeyPairStatus::PublicKeyReady);
+
+        let keypair_shares = app.shares(1000, id);
+        assert_eq!(keypair_shares.len(), CONTRIBUTORS.len());
+        assert_eq!(
+            make_public_key(scheme_to_string(1), &keypair_shares),
+            keypair.public_key
+        );
+    }
+}
+
+#[test]
+fn contributions_are_not_overwritten() {
+    let mut app = TestApp::new(test_genesis());
+    let pubkey_time = block_time(1) + test_params().min_pubkey_lead_time as i64;
+    assert_eq!(
+        app.block(1, &[new_process(1000, pubkey_time)]).0,
+        vec![true]
+    );
+
+    let (results, _) = app.block(2, &[contribute(CONTRIBUTORS[0], 1000, 1)]);
+    assert_eq!(results, vec![true]);
+    let stored = app.contribution(1000, 1, CONTRIBUTORS[0]).unwrap();
+    assert_eq!(stored.submitted_height, 2);
+    assert_eq!(stored.submitted_time, block_time(2));
+
+    // A second share from the same address is rejected, in the same block and later
+    let (results, _) = app.block(
+        3,
+        &[
+            contribute(CONTRIBUTORS[0], 1000, 1),
+            contribute(CONTRIBUTORS[1], 1000, 1),
+        ],
+    );
+    assert_eq!(results, vec![false, true]);
+    let (results, _) = app.block(4, &[contribute(CONTRIBUTORS[0], 1000, 1)]);
+    assert_eq!(results, vec![false]);
+
+    let unchanged = app.contribution(1000, 1, CONTRIBUTORS[0]).unwrap();
+    assert_eq!(unchanged.data, stored.data);
+    assert_eq!(unchanged.submitted_height, 2);
+    assert_eq!(unchanged.submitted_time, block_time(2));
+}
+
+#[test]
+fn rejected_txs_return_the_code_of_their_error() {
+    let mut app = TestApp::new(test_genesis());
+    let pubkey_time = block_time(1) + test_params().min_pubkey_lead_time as i64;
+    app.block(1, &[new_process(1000, pubkey_time)]);
+    app.block(2, &[contribute(CONTRIBUTORS[0], 1000, 1)]);
+
+    let mut unknown_beacon = new_process(1000, pubkey_time);
+    if let TimelockMessage::NewProcess(msg) = &mut unknown_beacon {
+        msg.beacon_id = 7;
+    }
+    let mut drand_default = new_process(1000, pubkey_time);
+    if let TimelockMessage::NewProcess(msg) = &mut drand_default {
+        msg.beacon_id = DRAND_DEFAULT_BEACON_ID;
+    }
+
+    for (tx, error) in [
+        (
+            contribute(CONTRIBUTORS[0], 1000, 1),
+            TimelockError::DuplicateContribution,
+        ),
+        (
+            contribute(CONTRIBUTORS[1], 1000, 2),
+            TimelockError::KeyPairNotFound,
+        ),
+        (unknown_beacon, TimelockError::BeaconNotFound),
+        (drand_default, TimelockError::BeaconNotTimelockable),
+    ] {
+        assert_eq!(app.deliver_tx(3, &tx).unwrap_err().code(), error.code());
+    }
+
+    let height = height_after(pubkey_time);
+    app.block(height, &[]);
+    assert_eq!(
+        app.deliver_tx(height + 1, &contribute(CONTRIBUTORS[1], 1000, 1))
+            .unwrap_err()
+            .code(),
+        TimelockError::ContributionWindowClosed.code()
+    );
+}
+
+#[test]
+fn missing_keypairs_are_reported_with_the_not_found_code() {
+    let mut app = TestApp::new(test_genesis());
+    let pubkey_time = block_time(1) + test_params().min_pubkey_lead_time as i64;
+    app.block(1, &[new_process(1000, pubkey_time)]);
+
+    let found = app.query(|keeper, ctx| keeper.query_keypair(ctx, 1000, 1, 1));
+    assert_eq!(found.unwrap().keypair.unwrap().owner, REQUESTER);
+
+    // The REST route answers 404 for this code only
+    let missing = app.query(|keeper, ctx| keeper.query_keypair(ctx, 1000, 1, 2));
+    assert_eq!(
+        missing.unwrap_err().code(),
+        TimelockError::KeyPairNotFound.code()
+    );
+}
+
+#[test]
+fn keypair_state_changes_emit_events() {
+    let mut app = TestApp::new(test_genesis());
+    let pubkey_time = block_time(1) + test_params().min_pubkey_lead_time as i64;
+    app.block(
+        1,
+        &[
+            new_process(1000, pubkey_time),
+            new_process(1000, pubkey_time),
+            cancel(1000, 2),
+        ],
+    );
+    assert_eq!(
+        event_kinds(&app.events),
+        vec![
+            "key_request_opened",
+            "key_request_opened",
+            "key_request_cancelled"
+        ]
+    );
+    let opened = &app.events[0];
+    assert_eq!(event_attribute(opened, "round"), "1000");
+    assert_eq!(event_attribute(opened, "id"), "1");
+    assert_eq!(event_attribute(opened, "owner"), REQUESTER);
+    assert_eq!(event_attribute(&app.events[2], "id"), "2");
+
+    // The rejected duplicate share adds no event
+    let (results, _) = app.block(
+        2,
+        &[
+            contribute(CONTRIBUTORS[0], 1000, 1),
+            contribute(CONTRIBUTORS[0], 1000, 1),
+            contribute(CONTRIBUTORS[1], 1000, 1),
+            contribute(CONTRIBUTORS[2], 1000, 1),
+        ],
+    );
+    assert_eq!(results, vec![true, false, true, true]);
+    assert_eq!(event_kinds(&app.events), vec!["contribution_accepted"; 3]);
+    assert_eq!(
+        event_attribute(&app.events[1], "contributor"),
+        CONTRIBUTORS[1]
+    );
+
+    app.block(height_after(pubkey_time), &[]);
+    assert!(event_kinds(&app.events).contains(&"public_key_finalized".to_string()));
+}
+
+#[test]
+fn rejected_contributions_are_named_in_the_tx_log() {
+    let mut app = TestApp::new(test_genesis());
+    let pubkey_time = block_time(1) + test_params().min_pubkey_lead_time as i64;
+    app.block(1, &[new_process(1000, pubkey_time)]);
+    app.block(2, &[contribute(CONTRIBUTORS[0], 1000, 1)]);
+
+    let error = app
+        .deliver_tx(3, &contribute(CONTRIBUTORS[0], 1000, 1))
+        .unwrap_err();
+    assert_eq!(error.code(), TimelockError::DuplicateContribution.code());
+    assert!(error.to_string().contains(&format!(
+        "contribution_rejected: round=1000 scheme=1 id=1 contributor={}",
+        CONTRIBUTORS[0]
+    )));
+
+    let error = app
+        .deliver_tx(3, &contribute(REQUESTER, 1000, 1))
+        .unwrap_err();
+    assert_eq!(error.code(), TimelockError::NotRegisteredContributor.code());
+}
+
+#[test]
+fn contributor_events_are_named_in_the_past_tense() {
+    let mut genesis = test_genesis();
+    genesis.authority = REQUESTER.into();
+    let mut app = TestApp::new(genesis);
+
+    app.block(
+        1,
+        &[
+            deregister(CONTRIBUTORS[2]),
+            register(CONTRIBUTORS[2]),
+            TimelockMessage::UpdateBeacon(MsgUpdateBeacon {
+                authority: AccAddress::from_bech32(REQUESTER).unwrap(),
+                beacon: default_beacons()[0].clone(),
+            }),
+        ],
+    );
+    assert_eq!(
+        event_kinds(&app.events),
+        vec![
+            "contributor_deregistered",
+            "contributor_registered",
+            "beacon_updated"
+        ]
+    );
+}
+
+#[test]
+fn scheduled_params_updates_are_applied_at_their_height() {
+    let mut genesis = test_genesis();
+    genesis.authority = REQUESTER.into();
+    let mut app = TestApp::new(genesis);
+    let max_multi_requests = test_params().max_multi_requests;
+
+    let update = TimelockMessage::UpdateParams(MsgUpdateParams {
+        authority: AccAddress::from_bech32(REQUESTER).unwrap(),
+        height: 4,
+        security_param: None,
+        min_contribution_threshold: None,
+        max_multi_requests: Some(max_multi_requests + 1),
+        min_pubkey_lead_time: None,
+        bond_denom: None,
+        contributor_bond: None,
+        threshold_numerator: None,
+        threshold_denominator: None,
+        request_fee: None,
+        min_participation_ratio: None,
+        retention_blocks: None,
+        max_prunes_per_block: None,
+        max_key_derivations_per_block: None,
+    });
+    let (accepted, _) = app.block(1, &[update]);
+    assert_eq!(accepted, vec![true]);
+
+    for height in 2..4 {
+        app.block(height, &[]);
+        let params = app.query(|keeper, ctx| keeper.query_params(ctx));
+        assert_eq!(params.max_multi_requests, max_multi_requests);
+        assert!(!event_kinds(&app.events).contains(&"params_updated".to_string()));
+    }
+
+    app.block(4, &[]);
+    let params = app.query(|keeper, ctx| keeper.query_params(ctx));
+    assert_eq!(params.max_multi_requests, max_multi_requests + 1);
+    assert_eq!(event_kinds(&app.events), vec!["params_updated"]);
+    assert_eq!(event_attribute(&app.events[0], "height"), "4");
+}
+
+#[test]
+fn key_requests_are_cancelled_by_their_requester_with_a_refund() {
+    let mut genesis = test_genesis();
+    genesis.params = Some(
+        Params {
+            request_fee: 10,
+            ..test_params()
+        }
+        .into(),
+    );
+    let mut app = TestApp::with_balances(genesis, &[(REQUESTER, 30)]);
+    let pubkey_time = block_time(1) + test_params().min_pubkey_lead_time as i64;
+    app.block(
+        1,
+        &[
+            new_process(1000, pubkey_time),
+            new_process(1000, pubkey_time),
+            new_canonical_process(1000, pubkey_time),
+        ],
+    );
+    assert_eq!(app.balance(REQUESTER), 0);
+
+    let not_owner = TimelockMessage::CancelKeyRequest(MsgCancelKeyRequest {
+        address: AccAddress::from_bech32(CONTRIBUTORS[0]).unwrap(),
+        round: 1000,
+        scheme: 1,
+        id: 1,
+    });
+    for (tx, error) in [
+        (not_owner, TimelockError::NotKeyPairOwner),
+        (
+            cancel(1000, 3),
+            TimelockError::CanonicalKeyPairNotCancellable,
+        ),
+    ] {
+        assert_eq!(app.deliver_tx(2, &tx).unwrap_err().code(), error.code());
+    }
+
+    let (results, _) = app.block(2, &[cancel(1000, 2)]);
+    assert_eq!(results, vec![true]);
+    assert_eq!(app.balance(REQUESTER), 10);
+    assert_eq!(
+        app.keypair(1000, 2).unwrap().status,
+        KeyPairStatus::Cancelled as i32
+    );
+
+    // Once the public key is made the request can't be withdrawn
+    app.block(
+        3,
+        &CONTRIBUTORS
+            .iter()
+            .map(|contributor| contribute(contributor, 1000, 1))
+            .collect::<Vec<_>>(),
+    );
+    let height = height_after(pubkey_time);
+    app.block(height, &[]);
+    assert_eq!(
+        app.deliver_tx(height + 1, &cancel(1000, 1))
+            .unwrap_err()
+            .code(),
+        TimelockError::InvalidStatusTransition.code()
+    );
+    // Only the fee of the canonical keypair, which failed without contributions, came back
+    assert_eq!(app.balance(REQUESTER), 20);
+}
+
+#[test]
+fn contributions_are_rejected_after_the_public_key_is_made() {
+    let mut app = TestApp::new(test_genesis());
+    let pubkey_time = block_time(1) + test_params().min_pubkey_lead_time as i64;
+    app.block(1, &[new_process(1000, pubkey_time)]);
+    app.block(
+        2,
+        &[
+            contribute(CONTRIBUTORS[0], 1000, 1),
+            contribute(CONTRIBUTORS[1], 1000, 1),
+        ],
+    );
+    let stored = app.contribution(1000, 1, CONTRIBUTORS[0]).unwrap();
+
+    // The begin blocker of the first block past the public key time makes the public key
+    let height = (pubkey_time - GENESIS_TIME) as u64 / BLOCK_PERIOD as u64 + 1;
+    let (results, _) = app.block(
+        height,
+        &[
+            contribute(CONTRIBUTORS[0], 1000, 1),
+            contribute(CONTRIBUTORS[2], 1000, 1),
+        ],
+    );
+    assert_eq!(results, vec![false, false]);
+
+    let unchanged = app.contribution(1000, 1, CONTRIBUTORS[0]).unwrap();
+    assert_eq!(unchanged.data, stored.data);
+    assert_eq!(unchanged.submitted_height, 2);
+    assert!(app.contribution(1000, 1, CONTRIBUTORS[2]).is_none());
+}
+
+fn new_canonical_process(round: u64, pubkey_time: i64) -> TimelockMessage {
+    let mut msg = new_process(round, pubkey_time);
+    if let TimelockMessage::NewProcess(msg) = &mut msg {
+        msg.canonical = true;
+    }
+    msg
+}
+
+#[test]
+fn keypairs_are_listed_for_every_address_which_requested_them() {
+    let mut app = TestApp::new(test_genesis());
+    let sharer = CONTRIBUTORS[0];
+    let pubkey_time = block_time(1) + test_params().min_pubkey_lead_time as i64;
+    let mut shared = new_canonical_process(1010, pubkey_time);
+    if let TimelockMessage::NewProcess(msg) = &mut shared {
+        msg.address = AccAddress::from_bech32(sharer).unwrap();
+    }
+    let (results, _) = app.block(
+        1,
+        &[
+            new_process(1000, pubkey_time),
+            new_canonical_process(1010, pubkey_time),
+            shared,
+        ],
+    );
+    assert_eq!(results, vec![true; 3]);
+
+    // The keypair records its first requester, the index lists it for the sharer too
+    assert_eq!(app.keypair(1010, 1).unwrap().owner, REQUESTER);
+    assert!(app.keypair(1010, 2).is_none());
+
+    let owned = |app: &TestApp, owner: &str, pagination: Option<PageRequest>| {
+        page_ids(app.query(|keeper, ctx| {
+            keeper.query_keypairs_by_owner(ctx, AccAddress::from_bech32(owner).unwrap(), pagination)
+        }))
+    };
+    assert_eq!(owned(&app, REQUESTER, None).0, vec![(1000, 1), (1010, 1)]);
+    assert_eq!(owned(&app, sharer, None).0, vec![(1010, 1)]);
+    assert!(owned(&app, CONTRIBUTORS[1], None).0.is_empty());
+
+    let (first, next_key) = owned(&app, REQUESTER, page(vec![], 0, 1));
+    assert_eq!(first, vec![(1000, 1)]);
+    let (second, next_key) = owned(&app, REQUESTER, page(next_key, 0, 1));
+    assert_eq!(second, vec![(1010, 1)]);
+    assert!(next_key.is_empty());
+}
+
+#[test]
+fn only_live_canonical_keypairs_are_shared() {
+    let mut app = TestApp::new(test_genesis());
+    let pubkey_time = block_time(1) + test_params().min_pubkey_lead_time as i64;
+    let (results, _) = app.block(
+        1,
+        &[
+            new_canonical_process(1000, pubkey_time),
+            new_canonical_process(1000, pubkey_time),
+        ],
+    );
+    assert_eq!(results, vec![true, true]);
+    assert!(app.keypair(1000, 2).is_none());
+
+    // Without contributions the canonical keypair fails, so a new one is made for the round
+    let height = height_after(pubkey_time);
+    app.block(height, &[]);
+    assert_eq!(
+        app.keypair(1000, 1).unwrap().status,
+        KeyPairStatus::Failed as i32
+    );
+
+    let pubkey_time = block_time(height + 1) + test_params().min_pubkey_lead_time as i64;
+    let (results, _) = app.block(
+        height + 1,
+        &[
+            new_canonical_process(1000, pubkey_time),
+            new_canonical_process(1000, pubkey_time),
+        ],
+    );
+    assert_eq!(results, vec![true, true]);
+    let replacement = app.keypair(1000, 2).unwrap();
+    assert!(replacement.canonical);
+    assert_eq!(replacement.status, KeyPairStatus::Requested as i32);
+    assert!(app.keypair(1000, 3).is_none());
+}
+
+/// Store rows of requested keypairs of the given rounds, ids and beacons
+fn keypair_rows(keypairs: &[(u64, u32, u32)]) -> Vec<(Vec<u8>, Vec<u8>)> {
+    keypairs
+        .iter()
+        .map(|(round, id, beacon_id)| {
+            let keypair = RawMsgKeyPair {
+                round: *round,
+                scheme: 1,
+                id: *id,
+                status: KeyPairStatus::Requested as i32,
+                beacon_id: *beacon_id,
+                ..RawMsgKeyPair::default()
+            };
+            (keypair_store_key(*round, 1, *id), keypair.encode_to_vec())
+        })
+        .collect()
+}
+
+fn page(key: Vec<u8>, offset: u64, limit: u64) -> Option<PageRequest> {
+    Some(PageRequest { key, offset, limit })
+}
+
+/// The rounds and ids of a page of keypairs, and its next key
+fn page_ids(response: QueryAllKeyPairsResponse) -> (Vec<(u64, u32)>, Vec<u8>) {
+    (
+        response
+            .keypairs
+            .iter()
+            .map(|keypair| (keypair.round, keypair.id))
+            .collect(),
+        response.pagination.unwrap().next_key,
+    )
+}
+
+#[test]
+fn round_ranges_include_both_ends_and_are_paged() {
+    let mut app = TestApp::new(test_genesis());
+    app.write(&keypair_rows(&[
+        (999, 1, QUICKNET_BEACON_ID),
+        (1000, 1, QUICKNET_BEACON_ID),
+        (1000, 2, QUICKNET_BEACON_ID),
+        (1001, 1, QUICKNET_BEACON_ID),
+        (1002, 1, DRAND_DEFAULT_BEACON_ID),
+        (1003, 1, QUICKNET_BEACON_ID),
+    ]));
+
+    let (first, next_key) = page_ids(app.query(|keeper, ctx| {
+        keeper.query_keypairs_by_round_range(ctx, 1000, 1002, page(vec![], 0, 3))
+    }));
+    assert_eq!(first, vec![(1000, 1), (1000, 2), (1001, 1)]);
+    assert!(!next_key.is_empty());
+
+    let (second, next_key) = page_ids(app.query(|keeper, ctx| {
+        keeper.query_keypairs_by_round_range(ctx, 1000, 1002, page(next_key, 0, 3))
+    }));
+    assert_eq!(second, vec![(1002, 1)]);
+    assert!(next_key.is_empty());
+
+    let (skipped, _) = page_ids(app.query(|keeper, ctx| {
+        keeper.query_keypairs_by_round_range(ctx, 1000, 1002, page(vec![], 1, 0))
+    }));
+    assert_eq!(skipped, vec![(1000, 2), (1001, 1), (1002, 1)]);
+
+    // The end of the range can't overflow
+    let (last, _) = page_ids(
+        app.query(|keeper, ctx| keeper.query_keypairs_by_round_range(ctx, 1003, u64::MAX, None)),
+    );
+    assert_eq!(last, vec![(1003, 1)]);
+}
+
+#[test]
+fn time_ranges_are_mapped_to_the_rounds_of_each_beacon_and_paged() {
+    let beacons = default_beacons();
+    let (quicknet, default) = (&beacons[0], &beacons[1]);
+    // Quicknet publishes round 1002 at `to` and the default beacon publishes its round at `to`
+    let from = quicknet.genesis_time + 999 * quicknet.period as i64;
+    let to = from + 6;
+    let default_round = (to - default.genesis_time) as u64 / default.period as u64 + 1;
+    assert_eq!(
+        default.genesis_time + (default_round as i64 - 1) * default.period as i64,
+        to
+    );
+
+    let mut app = TestApp::new(test_genesis());
+    app.write(&keypair_rows(&[
+        (999, 1, QUICKNET_BEACON_ID),
+        (1000, 1, QUICKNET_BEACON_ID),
+        (1000, 2, QUICKNET_BEACON_ID),
+        (1001, 1, QUICKNET_BEACON_ID),
+        (1002, 1, DRAND_DEFAULT_BEACON_ID),
+        (1003, 1, QUICKNET_BEACON_ID),
+        (default_round, 1, DRAND_DEFAULT_BEACON_ID),
+    ]));
+
+    // Pages run through the beacons in id order
+    let (first, next_key) = page_ids(app.query(|keeper, ctx| {
+        keeper.query_keypairs_by_time_range(ctx, from, to, page(vec![], 0, 2))
+    }));
+    assert_eq!(first, vec![(1000, 1), (1000, 2)]);
+    assert!(!next_key.is_empty());
+
+    let (second, next_key) = page_ids(app.query(|keeper, ctx| {
+        keeper.query_keypairs_by_time_range(ctx, from, to, page(next_key, 0, 2))
+    }));
+    assert_eq!(second, vec![(1001, 1), (default_round, 1)]);
+    assert!(next_key.is_empty());
+
+    // Rounds published on the bounds are included, the ones just outside are not
+    let (inner, _) = page_ids(
+        app.query(|keeper, ctx| keeper.query_keypairs_by_time_range(ctx, from + 1, to - 1, None)),
+    );
+    assert_eq!(inner, vec![(1001, 1)]);
+}
+
+#[test]
+fn ids_of_archived_keypairs_are_not_reused() {
+    let mut genesis = test_genesis();
+    genesis.archived_keypairs = vec![RawArchivedKeyPair {
+        round: 1000,
+        scheme: 1,
+        id: 1,
+        ..RawArchivedKeyPair::default()
+    }];
+    let mut app = TestApp::new(genesis);
+
+    let pubkey_time = block_time(1) + test_params().min_pubkey_lead_time as i64;
+    let (results, _) = app.block(
+        1,
+        &[
+            new_process(1000, pubkey_time),
+            new_process(1000, pubkey_time),
+        ],
+    );
+    assert_eq!(results, vec![true, true]);
+
+    assert!(app.keypair(1000, 1).is_none());
+    assert_eq!(app.keypair(1000, 2).unwrap().owner, REQUESTER);
+    assert_eq!(app.keypair(1000, 3).unwrap().owner, REQUESTER);
+}
+
+#[test]
+fn exported_genesis_is_imported_unchanged() {
+    let mut app = TestApp::new(test_genesis());
+    let pubkey_time = block_time(1) + test_params().min_pubkey_lead_time as i64;
+    app.block(
+        1,
+        &[
+            new_process(1000, pubkey_time),
+            new_process(1000, pubkey_time),
+            new_process(1010, pubkey_time + BLOCK_PERIOD * 10),
+        ],
+    );
+    app.block(
+        2,
+        &[
+            contribute(CONTRIBUTORS[0], 1000, 1),
+            contribute(CONTRIBUTORS[1], 1000, 1),
+            contribute(CONTRIBUTORS[2], 1000, 1),
+            contribute(CONTRIBUTORS[0], 1010, 1),
+        ],
+    );
+    // Past the public key time of round 1000 the first keypair has its public key and the
+    // second failed, the keypair of round 1010 is still collecting
+    app.block(height_after(pubkey_time), &[]);
+    assert!(!app.keypair(1000, 1).unwrap().public_key.is_empty());
+    assert_eq!(
+        app.keypair(1000, 2).unwrap().status,
+        KeyPairStatus::Failed as i32
+    );
+    assert_eq!(
+        app.keypair(1010, 1).unwrap().status,
+        KeyPairStatus::Collecting as i32
+    );
+
+    let exported = app.query(|keeper, ctx| keeper.export_genesis(ctx));
+    exported.validate().unwrap();
+    let mut imported = TestApp::new(exported.clone());
+    let reexported = imported.query(|keeper, ctx| keeper.export_genesis(ctx));
+
+    assert_eq!(reexported.encode_to_vec(), exported.encode_to_vec());
+    for (round, id) in [(1000, 1), (1000, 2), (1010, 1)] {
+        assert_eq!(
+            imported.keypair(round, id).unwrap().encode_to_vec(),
+            app.keypair(round, id).unwrap().encode_to_vec()
+        );
+    }
+    for contributor in CONTRIBUTORS {
+        assert_eq!(
+            imported.contribution(1000, 1, contributor).unwrap().data,
+            app.contribution(1000, 1, contributor).unwrap().data
+        );
+    }
+}
+
+#[test]
+fn genesis_keypairs_must_match_their_contributions_and_keys() {
+    let keypair = |status: KeyPairStatus, public_key: &str, private_key: &str| RawMsgKeyPair {
+        round: 1000,
+        scheme: 1,
+        id: 1,
+        public_key: public_key.into(),
+        private_key: private_key.into(),
+        status: status as i32,
+        ..RawMsgKeyPair::default()
+    };
+    let contribution = RawMsgContribution {
+        round: 1000,
+        scheme: 1,
+        id: 1,
+        address: CONTRIBUTORS[0].into(),
+        ..RawMsgContribution::default()
+    };
+
+    for (keypair, contributed, valid) in [
+        (keypair(KeyPairStatus::Requested, "", ""), false, true),
+        (keypair(KeyPairStatus::Requested, "", ""), true, false),
+        (keypair(KeyPairStatus::Collecting, "", ""), true, true),
+        (keypair(KeyPairStatus::Collecting, "", ""), false, false),
+        (keypair(KeyPairStatus::Collecting, "pk", ""), true, false),
+        (keypair(KeyPairStatus::PublicKeyReady, "pk", ""), true, true),
+        (keypair(KeyPairStatus::PublicKeyReady, "", ""), true, false),
+        (
+            keypair(KeyPairStatus::AwaitingBeacon, "pk", "sk"),
+            true,
+            false,
+        ),
+        (keypair(KeyPairStatus::Released, "pk", "sk"), true, true),
+        (keypair(KeyPairStatus::Released, "pk", ""), true, false),
+        (keypair(KeyPairStatus::Failed, "", ""), true, true),
+        (keypair(KeyPairStatus::Cancelled, "pk", ""), true, false),
+    ] {
+        let status = keypair.status;
+        let mut genesis = test_genesis();
+        genesis.keypairs = vec![keypair];
+        if contributed {
+            genesis.contributions = vec![contribution.clone()];
+        }
+
+        assert_eq!(
+            genesis.validate().is_ok(),
+            valid,
+            "status {} with contributions: {}",
+            status,
+            contributed
+        );
+    }
+}
+
+/// Requests a keypair in each of `count` rounds from `start_round` on
+fn multi_new_process(start_round: u64, count: u32, pubkey_time: i64) -> TimelockMessage {
+    TimelockMessage::MultiNewProcess(MsgMultiNewProcess {
+        address: AccAddress::from_bech32(REQUESTER).unwrap(),
+        startround: start_round,
+        reqnum: count,
+        roundstep: 10,
+        schemes: vec![1],
+        pubkey_time,
+        beacon_id: QUICKNET_BEACON_ID,
+    })
+}
+
+fn register(contributor: &str) -> TimelockMessage {
+    TimelockMessage::RegisterContributor(MsgRegisterContributor {
+        address: AccAddress::from_bech32(contributor).unwrap(),
+    })
+}
+
+fn deregister(contributor: &str) -> TimelockMessage {
+    TimelockMessage::DeregisterContributor(MsgDeregisterContributor {
+        address: AccAddress::from_bech32(contributor).unwrap(),
+    })
+}
+
+#[test]
+fn contributions_of_unregistered_senders_are_rejected() {
+    let mut genesis = test_genesis();
+    genesis.contributors.truncate(2);
+    let outsider = CONTRIBUTORS[2];
+    let mut app = TestApp::new(genesis);
+
+    let pubkey_time = block_time(1) + test_params().min_pubkey_lead_time as i64;
+    app.block(1, &[new_process(1000, pubkey_time)]);
+
+    let error = app
+        .deliver_tx(2, &contribute(outsider, 1000, 1))
+        .unwrap_err();
+    assert_eq!(error.code(), TimelockError::NotRegisteredContributor.code());
+    assert!(app.contribution(1000, 1, outsider).is_none());
+
+    // Leaving needs a registration too, and a deregistered contributor can't contribute anymore
+    let (results, _) = app.block(
+        2,
+        &[
+            deregister(outsider),
+            deregister(CONTRIBUTORS[1]),
+            contribute(CONTRIBUTORS[1], 1000, 1),
+            contribute(CONTRIBUTORS[0], 1000, 1),
+        ],
+    );
+    assert_eq!(results, vec![false, true, false, true]);
+    assert!(app.contribution(1000, 1, CONTRIBUTORS[1]).is_none());
+    assert!(app.contribution(1000, 1, CONTRIBUTORS[0]).is_some());
+}
+
+#[test]
+fn bonds_move_into_and_out_of_the_module_account() {
+    let mut genesis = test_genesis();
+    genesis.contributors.truncate(2);
+    genesis.params = Some(
+        Params {
+            contributor_bond: 100,
+            ..test_params()
+        }
+        .into(),
+    );
+    let newcomer = CONTRIBUTORS[2];
+    let mut app = TestApp::with_balances(genesis, &[(newcomer, 150)]);
+
+    assert_eq!(app.block(1, &[register(newcomer)]).0, vec![true]);
+    assert_eq!(app.balance(newcomer), 50);
+    assert_eq!(app.balance(TIMELOCK_MODULE_ADDRESS), 100);
+
+    // Registering twice would bond twice
+    assert_eq!(app.block(2, &[register(newcomer)]).0, vec![false]);
+    assert_eq!(app.balance(newcomer), 50);
+    assert_eq!(app.block(3, &[deregister(newcomer)]).0, vec![true]);
+    assert_eq!(app.balance(newcomer), 150);
+    assert_eq!(app.balance(TIMELOCK_MODULE_ADDRESS), 0);
+
+    // A sender who can't pay the bond isn't registered
+    assert_eq!(app.block(4, &[register(REQUESTER)]).0, vec![false]);
+    assert_eq!(app.balance(TIMELOCK_MODULE_ADDRESS), 0);
+    let contributor = app.query(|keeper, ctx| {
+        keeper.query_contributor_stats(ctx, AccAddress::from_bech32(REQUESTER).unwrap())
+    });
+    assert!(contributor.contributor.is_none());
+}
+
+#[test]
+fn contributors_are_jailed_by_their_recent_windows_and_forfeit_the_bond() {
+    let mut genesis = test_genesis();
+    genesis.contributors.truncate(2);
+    genesis.params = Some(
+        Params {
+            contributor_bond: 100,
+            min_participation_ratio: 500,
+            ..test_params()
+        }
+        .into(),
+    );
+    let absent = CONTRIBUTORS[2];
+    let mut app = TestApp::with_balances(genesis, &[(absent, 200)]);
+
+    assert_eq!(app.block(1, &[register(absent)]).0, vec![true]);
+    assert_eq!(app.balance(absent), 100);
+
+    // The other contributors share in every window, the absent one misses all of them
+    let pubkey_time = block_time(2) + test_params().min_pubkey_lead_time as i64;
+    let rounds: Vec<u64> = (0..10).map(|step| 1000 + step * 10).collect();
+    app.block(2, &[multi_new_process(1000, 10, pubkey_time)]);
+    let shares: Vec<TimelockMessage> = rounds
+        .iter()
+        .flat_map(|round| {
+            [
+                contribute(CONTRIBUTORS[0], *round, 1),
+                contribute(CONTRIBUTORS[1], *round, 1),
+            ]
+        })
+        .collect();
+    app.block(3, &shares);
+
+    let mut height = height_after(pubkey_time);
+    app.block(height, &[]);
+    let stats = app.query(|keeper, ctx| {
+        keeper.query_contributor_stats(ctx, AccAddress::from_bech32(absent).unwrap())
+    });
+    assert!(stats.contributor.unwrap().jailed);
+    assert_eq!(stats.stats.as_ref().unwrap().missed_count, 10);
+    assert_eq!(stats.uptime, 0);
+    for contributor in &CONTRIBUTORS[..2] {
+        let stats = app.query(|keeper, ctx| {
+            keeper.query_contributor_stats(ctx, AccAddress::from_bech32(contributor).unwrap())
+        });
+        assert!(!stats.contributor.unwrap().jailed);
+        assert_eq!(stats.uptime, 1000);
+    }
+
+    // Leaving while jailed forfeits the bond, bonding again starts a new run of windows
+    height += 1;
+    let (results, _) = app.block(height, &[deregister(absent), register(absent)]);
+    assert_eq!(results, vec![true, true]);
+    assert_eq!(app.balance(absent), 0);
+
+    let pubkey_time = block_time(height + 1) + test_params().min_pubkey_lead_time as i64;
+    app.block(height + 1, &[multi_new_process(2000, 1, pubkey_time)]);
+    app.block(height_after(pubkey_time), &[]);
+
+    let stats = app.query(|keeper, ctx| {
+        keeper.query_contributor_stats(ctx, AccAddress::from_bech32(absent).unwrap())
+    });
+    let counts = stats.stats.unwrap();
+    assert!(!stats.contributor.unwrap().jailed);
+    assert_eq!(counts.missed_count, 11);
+    assert_eq!(counts.recent_windows, vec![false]);
+}
+
+#[test]
+fn failed_and_cancelled_keypairs_are_archived_with_their_contributions() {
+    let mut genesis = test_genesis();
+    genesis.params = Some(
+        Params {
+            retention_blocks: 2,
+            ..test_params()
+        }
+        .into(),
+    );
+    let mut app = TestApp::new(genesis);
+    let pubkey_time = block_time(1) + test_params().min_pubkey_lead_time as i64;
+    app.block(
+        1,
+        &[
+            new_process(1000, pubkey_time),
+            new_process(1000, pubkey_time),
+        ],
+    );
+    app.block(
+        2,
+        &[
+            contribute(CONTRIBUTORS[0], 1000, 1),
+            contribute(CONTRIBUTORS[0], 1000, 2),
+            cancel(1000, 2),
+        ],
+    );
+
+    // The cancelled keypair is archived when its retention period ends
+    app.block(3, &[]);
+    assert!(app.keypair(1000, 2).is_some());
+    app.block(4, &[]);
+    assert!(app.keypair(1000, 2).is_none());
+    assert!(app.contribution(1000, 2, CONTRIBUTORS[0]).is_none());
+
+    // The other one fails with a single share and is archived two blocks later
+    let height = height_after(pubkey_time);
+    app.block(height, &[]);
+    app.block(height + 1, &[]);
+    assert!(app.contribution(1000, 1, CONTRIBUTORS[0]).is_some());
+    app.block(height + 2, &[]);
+    assert!(app.keypair(1000, 1).is_none());
+    assert!(app.contribution(1000, 1, CONTRIBUTORS[0]).is_none());
+
+    let archived = app.query(|keeper, ctx| keeper.query_archived_keypairs(ctx, None));
+    let statuses: Vec<(u32, i32)> = archived
+        .keypairs
+        .iter()
+        .map(|keypair| (keypair.id, keypair.status))
+        .collect();
+    assert_eq!(
+        statuses,
+        vec![
+            (1, KeyPairStatus::Failed as i32),
+            (2, KeyPairStatus::Cancelled as i32)
+        ]
+    );
+    let stats = app.query(|keeper, ctx| keeper.query_prune_stats(ctx));
+    assert_eq!(stats.archived_keypairs, 2);
+    assert_eq!(stats.pruned_contributions, 2);
+    assert_eq!(stats.pending, 0);
+}
+
+#[test]
+fn pruning_stops_at_the_per_block_limit() {
+    let mut genesis = test_genesis();
+    genesis.params = Some(
+        Params {
+            retention_blocks: 1,
+            max_prunes_per_block: 2,
+            ..test_params()
+        }
+        .into(),
+    );
+    genesis.keypairs = released_keypairs(5);
+    let mut app = TestApp::new(genesis);
+
+    // The keypairs of the genesis are due at height 1, two are archived per block
+    for (height, archived, pending) in [(1, 2, 3), (2, 4, 1), (3, 5, 0), (4, 5, 0)] {
+        app.block(height, &[]);
+        let stats = app.query(|keeper, ctx| keeper.query_prune_stats(ctx));
+        assert_eq!(
+            (stats.archived_keypairs, stats.pending),
+            (archived, pending)
+        );
+    }
+    assert!(app.keypair(1000, 5).is_none());
+}
+
+fn released_keypairs(count: u32) -> Vec<RawMsgKeyPair> {
+    (1..=count)
+        .map(|id| RawMsgKeyPair {
+            round: 1000,
+            scheme: 1,
+            id,
+            public_key: "public key".into(),
+            private_key: "private key".into(),
+            status: KeyPairStatus::Released as i32,
+            ..RawMsgKeyPair::default()
+        })
+        .collect()
+}
+
+/// Rows of keypairs which are due for their public key, with ids from 1, and of keypairs whose
+/// LOE data is stored, with ids from 101
+fn queued_work(public_keys: u32, private_keys: u32) -> Vec<(Vec<u8>, Vec<u8>)> {
+    let mut rows = vec![(
+        loe_data_store_key(QUICKNET_BEACON_ID, 1000),
+        RawMsgLoeData {
+            round: 1000,
+            signature: "signature".into(),
+            beacon_id: QUICKNET_BEACON_ID,
+            ..RawMsgLoeData::default()
+        }
+        .encode_to_vec(),
+    )];
+
+    for id in 1..=public_keys {
+        let keypair = RawMsgKeyPair {
+            round: 1000,
+            scheme: 1,
+            id,
+            pubkey_time: GENESIS_TIME,
+            status: KeyPairStatus::Requested as i32,
+            beacon_id: QUICKNET_BEACON_ID,
+            ..RawMsgKeyPair::default()
+        };
+        rows.push((keypair_store_key(1000, 1, id), keypair.encode_to_vec()));
+        rows.push((pubkey_queue_store_key(GENESIS_TIME, 1000, 1, id), vec![]));
+    }
+
+    for id in 101..101 + private_keys {
+        let keypair = RawMsgKeyPair {
+            round: 1000,
+            scheme: 1,
+            id,
+            pubkey_time: GENESIS_TIME,
+            public_key: "public key".into(),
+            status: KeyPairStatus::PublicKeyReady as i32,
+            beacon_id: QUICKNET_BEACON_ID,
+            ..RawMsgKeyPair::default()
+        };
+        rows.push((keypair_store_key(1000, 1, id), keypair.encode_to_vec()));
+        rows.push((loe_queue_store_key(QUICKNET_BEACON_ID, 1000, 1, id), vec![]));
+    }
+
+    rows
+}
+
+#[test]
+fn derivation_budget_is_split_between_public_and_private_keys() {
+    // With a budget of 4 each kind gets 2, and the share one kind doesn't use goes to the other
+    for (queued, made, pending) in [
+        ((4, 4), (2, 2), (2, 2)),
+        ((1, 6), (1, 3), (0, 3)),
+        ((6, 1), (3, 1), (3, 0)),
+        ((6, 0), (4, 0), (2, 0)),
+        ((2, 2), (2, 2), (0, 0)),
+    ] {
+        let mut genesis = test_genesis();
+        genesis.params = Some(
+            Params {
+                max_key_derivations_per_block: 4,
+                ..test_params()
+            }
+            .into(),
+        );
+        let mut app = TestApp::new(genesis);
+        app.write(&queued_work(queued.0, queued.1));
+
+        let (public, private) = app.empty_keypairs(1);
+        assert_eq!((public.len(), private.len()), made, "queued {:?}", queued);
+        // Each kind is taken in queue order
+        assert!(public.iter().copied().eq(1..=made.0 as u32));
+        assert!(private.iter().copied().eq(101..101 + made.1 as u32));
+
+        let work = app.query(|keeper, ctx| keeper.query_pending_work(ctx));
+        assert_eq!(
+            (work.public_keys, work.private_keys, work.height),
+            (pending.0, pending.1, 1),
+            "queued {:?}",
+            queued
+        );
+    }
+}
+
+#[test]
+fn due_keys_which_dont_fit_are_made_in_the_next_blocks() {
+    let mut genesis = test_genesis();
+    genesis.params = Some(
+        Params {
+            max_key_derivations_per_block: 2,
+            ..test_params()
+        }
+        .into(),
+    );
+    let mut app = TestApp::new(genesis);
+
+    let pubkey_time = block_time(1) + test_params().min_pubkey_lead_time as i64;
+    let requests = vec![new_process(1000, pubkey_time); 3];
+    let (accepted, _) = app.block(1, &requests);
+    assert_eq!(accepted, vec![true; 3]);
+
+    // No contributions were sent, so every keypair fails when its public key is due
+    let due = height_after(pubkey_time);
+    app.block(due, &[]);
+    let work = app.query(|keeper, ctx| keeper.query_pending_work(ctx));
+    assert_eq!(
+        (work.public_keys, work.private_keys, work.height),
+        (1, 0, due)
+    );
+    assert_eq!(
+        app.keypair(1000, 2).unwrap().status(),
+        KeyPairStatus::Failed
+    );
+    assert_eq!(
+        app.keypair(1000, 3).unwrap().status(),
+        KeyPairStatus::Requested
+    );
+
+    app.block(due + 1, &[]);
+    let work = app.query(|keeper, ctx| keeper.query_pending_work(ctx));
+    assert_eq!(
+        (work.public_keys, work.private_keys, work.height),
+        (0, 0, due + 1)
+    );
+    assert_eq!(
+        app.keypair(1000, 3).unwrap().status(),
+        KeyPairStatus::Failed
+    );
+}
+
+/// A contribution of every contributor to each keypair
+fn contributions(keypairs: &[RawMsgKeyPair]) -> Vec<RawMsgContribution> {
+    keypairs
+        .iter()
+        .flat_map(|keypair| {
+            CONTRIBUTORS.iter().map(|contributor| RawMsgContribution {
+                address: contributor.to_string(),
+                round: keypair.round,
+                scheme: keypair.scheme,
+                id: keypair.id,
+                data: vec![0; 64],
+                ..RawMsgContribution::default()
+            })
+        })
+        .collect()
+}
+
+/// Run with `cargo test -p timelock --release -- --ignored --nocapture`. The begin blocker only
+/// walks the pending work queues, so its cost must not grow with the released keypairs and
+/// their contributions.
+#[test]
+#[ignore]
+fn block_cost_stays_flat_as_history_grows() {
+    const BLOCKS: u64 = 20;
+    let mut costs = vec![];
+
+    for history in [100, 1_000, 10_000] {
+        let mut genesis = test_genesis();
+        genesis.keypairs = released_keypairs(history);
+        genesis.contributions = contributions(&genesis.keypairs);
+        let mut app = TestApp::new(genesis);
+
+        let started = Instant::now();
+        for height in 1..=BLOCKS {
+            app.block(height, &[]);
+        }
+        let cost = started.elapsed() / BLOCKS as u32;
+
+        println!(
+            "{:>7} released keypairs with {} contributions each: {:?} per block",
+            history,
+            CONTRIBUTORS.len(),
+            cost
+        );
+        costs.push(cost);
+    }
+
+    // Generous bound so the timing noise of a shared machine doesn't fail it, a scan of the
+    // history would be about a hundred times slower at the largest size
+    let smallest = costs.iter().min().unwrap();
+    let largest = costs.iter().max().unwrap();
+    assert!(*largest < *smallest * 10 + Duration::from_millis(1));
+}
+
+#[test]
+fn instances_agree_on_every_block() {
+    let pubkey_time = block_time(1) + test_params().min_pubkey_lead_time as i64;
+    let mut blocks = vec![(
+        1,
+        vec![
+            new_process(1020, pubkey_time),
+            new_process(1010, pubkey_time),
+            new_process(1010, pubkey_time),
+            TimelockMessage::MultiNewProcess(MsgMultiNewProcess {
+                address: AccAddress::from_bech32(REQUESTER).unwrap(),
+                startround: 1000,
+                reqnum: 3,
+                roundstep: 10,
+                schemes: vec![1, 2],
+                pubkey_time,
+                beacon_id: QUICKNET_BEACON_ID,
+            }),
+        ],
+    )];
+
+    // The shares are random, so they are made once and every instance gets the same txs
+    let mut shares = vec![];
+    for (round, id) in [(1020, 1), (1010, 2), (1010, 1), (1000, 1), (1020, 2)] {
+        for contributor in CONTRIBUTORS {
+            shares.push(contribute(contributor, round, id));
+        }
+    }
+    blocks.push((2, shares));
+
+    // Past the public key time the begin blocker makes the public keys and fails the rest
+    let last_height = (pubkey_time - GENESIS_TIME) as u64 / BLOCK_PERIOD as u64 + 2;
+    for height in 3..=last_height {
+        blocks.push((height, vec![]));
+    }
+
+    let mut apps: Vec<TestApp> = (0..3).map(|_| TestApp::new(test_genesis())).collect();
+
+    for (height, txs) in &blocks {
+        let results: Vec<_> = apps.iter_mut().map(|app| app.block(*height, txs)).collect();
+        assert!(
+            results.iter().all(|result| *result == results[0]),
+            "instances disagree at height {}",
+            height
+        );
+    }
+
+    let keypair = apps[0].keypair(1010, 2).unwrap();
+    assert_eq!(keypair.status(), KeyPairStatus::PublicKeyReady);
+    assert!(!keypair.public_key.is_empty());
+}
@@ -1,28 +1,47 @@
+mod beacon;
 mod client;
 mod config;
+mod error;
+mod events;
+mod genesis;
 mod handler;
 mod keeper;
 mod message;
+mod params;
 pub mod proto;
 pub mod utils;
 
+pub use beacon::*;
 pub use client::*;
 pub use config::*;
+pub use error::*;
+pub use events::*;
+pub use genesis::*;
 pub use handler::*;
 pub use keeper::*;
 pub use message::*;
+pub use params::*;
 
-// LOE Parameters from https://api.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/info
-// This is the "quicknet"
-pub const LOE_URL: &str =
-    "https://api.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/";
-const LOE_PUBLIC_KEY: &str = "83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a";
-pub const LOE_GENESIS_TIME: u32 = 1692803367;
-pub const LOE_PERIOD: u32 = 3;
-const SECURITY_PARAM: usize = 10;
+/// Address of the account which holds the contributor bonds and the escrowed request fees. It
+/// is derived like a cosmos module account address, the first 20 bytes of sha256("timelock").
+pub const TIMELOCK_MODULE_ADDRESS: &str = "cosmos1kz0zjy5p3ay05hjvj83tk77sa2n60usqu4qqk7";
 
 // Key Prefixes
-const CONTRIBUTION_THRESHOLD_KEY: [u8; 1] = [0];
 const PARTICIPANT_DATA_KEY: [u8; 1] = [1];
 const KEYPAIR_DATA_KEY: [u8; 1] = [2];
 const LOE_DATA_KEY: [u8; 1] = [3];
+const AUTHORITY_KEY: [u8; 1] = [4];
+const PENDING_PARAMS_KEY: [u8; 1] = [5];
+const CONTRIBUTOR_DATA_KEY: [u8; 1] = [6];
+const FEE_ESCROW_KEY: [u8; 1] = [7];
+const PARTICIPATION_KEY: [u8; 1] = [8];
+// Keypairs in a final status, released, failed or cancelled, by the height they are archived at
+const RELEASED_QUEUE_KEY: [u8; 1] = [9];
+const ARCHIVED_KEYPAIR_KEY: [u8; 1] = [10];
+const PRUNE_STATS_KEY: [u8; 1] = [11];
+const PUBKEY_QUEUE_KEY: [u8; 1] = [12];
+const LOE_QUEUE_KEY: [u8; 1] = [13];
+const PENDING_WORK_KEY: [u8; 1] = [14];
+const STORE_VERSION_KEY: [u8; 1] = [15];
+const OWNER_INDEX_KEY: [u8; 1] = [16];
+const BEACON_KEY: [u8; 1] = [17];
@@ -3,7 +3,10 @@ use ibc_proto::{google::protobuf::Any, protobuf::Protobuf};
 use proto_types::AccAddress;
 use serde::Serialize;
 
-use crate::proto::tlcs::v1beta1::{MsgContribution, MsgLoeData, MsgMultiNewProcess, MsgNewProcess};
+use crate::proto::tlcs::v1beta1::{
+    MsgCancelKeyRequest, MsgContribution, MsgDeregisterContributor, MsgLoeData, MsgMultiNewProcess,
+    MsgNewProcess, MsgRegisterContributor, MsgUpdateBeacon, MsgUpdateParams,
+};
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(tag = "@type")]
@@ -16,6 +19,16 @@ pub enum Message {
     Participate(MsgContribution),
     #[serde(rename = "/tlcs.timelock.v1beta1.MsgLoeData")]
     SubmitLoeData(MsgLoeData),
+    #[serde(rename = "/tlcs.timelock.v1beta1.MsgUpdateParams")]
+    UpdateParams(MsgUpdateParams),
+    #[serde(rename = "/tlcs.timelock.v1beta1.MsgRegisterContributor")]
+    RegisterContributor(MsgRegisterContributor),
+    #[serde(rename = "/tlcs.timelock.v1beta1.MsgDeregisterContributor")]
+    DeregisterContributor(MsgDeregisterContributor),
+    #[serde(rename = "/tlcs.timelock.v1beta1.MsgCancelKeyRequest")]
+    CancelKeyRequest(MsgCancelKeyRequest),
+    #[serde(rename = "/tlcs.timelock.v1beta1.MsgUpdateBeacon")]
+    UpdateBeacon(MsgUpdateBeacon),
 }
 
 impl proto_messages::cosmos::tx::v1beta1::Message for Message {
@@ -25,6 +38,11 @@ impl proto_messages::cosmos::tx::v1beta1::Message for Message {
             Message::MultiNewProcess(msg) => vec![&msg.address],
             Message::Participate(msg) => vec![&msg.address],
             Message::SubmitLoeData(msg) => vec![&msg.address],
+            Message::UpdateParams(msg) => vec![&msg.authority],
+            Message::RegisterContributor(msg) => vec![&msg.address],
+            Message::DeregisterContributor(msg) => vec![&msg.address],
+            Message::CancelKeyRequest(msg) => vec![&msg.address],
+            Message::UpdateBeacon(msg) => vec![&msg.authority],
         }
     }
 
@@ -34,6 +52,29 @@ impl proto_messages::cosmos::tx::v1beta1::Message for Message {
             Message::MultiNewProcess(_) => Ok(()),
             Message::Participate(_) => Ok(()),
             Message::SubmitLoeData(_) => Ok(()),
+            Message::UpdateParams(msg) => {
+                if msg.security_param.is_none()
+                    && msg.min_contribution_threshold.is_none()
+                    && msg.max_multi_requests.is_none()
+                    && msg.min_pubkey_lead_time.is_none()
+                    && msg.bond_denom.is_none()
+                    && msg.contributor_bond.is_none()
+                    && msg.threshold_numerator.is_none()
+                    && msg.threshold_denominator.is_none()
+                    && msg.request_fee.is_none()
+                    && msg.min_participation_ratio.is_none()
+                    && msg.retention_blocks.is_none()
+                    && msg.max_prunes_per_block.is_none()
+                    && msg.max_key_derivations_per_block.is_none()
+                {
+                    return Err("params update doesn't change any params".into());
+                }
+                Ok(())
+            }
+            Message::RegisterContributor(_) => Ok(()),
+            Message::DeregisterContributor(_) => Ok(()),
+            Message::CancelKeyRequest(_) => Ok(()),
+            Message::UpdateBeacon(_) => Ok(()),
         }
     }
 }
@@ -57,6 +98,26 @@ impl From<Message> for Any {
                 type_url: "/tlcs.timelock.v1beta1.MsgLoeData".to_string(),
                 value: msg.encode_vec(),
             },
+            Message::UpdateParams(msg) => Any {
+                type_url: "/tlcs.timelock.v1beta1.MsgUpdateParams".to_string(),
+                value: msg.encode_vec(),
+            },
+            Message::RegisterContributor(msg) => Any {
+                type_url: "/tlcs.timelock.v1beta1.MsgRegisterContributor".to_string(),
+                value: msg.encode_vec(),
+            },
+            Message::DeregisterContributor(msg) => Any {
+                type_url: "/tlcs.timelock.v1beta1.MsgDeregisterContributor".to_string(),
+                value: msg.encode_vec(),
+            },
+            Message::CancelKeyRequest(msg) => Any {
+                type_url: "/tlcs.timelock.v1beta1.MsgCancelKeyRequest".to_string(),
+                value: msg.encode_vec(),
+            },
+            Message::UpdateBeacon(msg) => Any {
+                type_url: "/tlcs.timelock.v1beta1.MsgUpdateBeacon".to_string(),
+                value: msg.encode_vec(),
+            },
         }
     }
 }
@@ -82,6 +143,26 @@ impl TryFrom<Any> for Message {
                 let msg = MsgLoeData::decode::<Bytes>(value.value.clone().into())?;
                 Ok(Message::SubmitLoeData(msg))
             }
+            "/tlcs.timelock.v1beta1.MsgUpdateParams" => {
+                let msg = MsgUpdateParams::decode::<Bytes>(value.value.clone().into())?;
+                Ok(Message::UpdateParams(msg))
+            }
+            "/tlcs.timelock.v1beta1.MsgRegisterContributor" => {
+                let msg = MsgRegisterContributor::decode::<Bytes>(value.value.clone().into())?;
+                Ok(Message::RegisterContributor(msg))
+            }
+            "/tlcs.timelock.v1beta1.MsgDeregisterContributor" => {
+                let msg = MsgDeregisterContributor::decode::<Bytes>(value.value.clone().into())?;
+                Ok(Message::DeregisterContributor(msg))
+            }
+            "/tlcs.timelock.v1beta1.MsgCancelKeyRequest" => {
+                let msg = MsgCancelKeyRequest::decode::<Bytes>(value.value.clone().into())?;
+                Ok(Message::CancelKeyRequest(msg))
+            }
+            "/tlcs.timelock.v1beta1.MsgUpdateBeacon" => {
+                let msg = MsgUpdateBeacon::decode::<Bytes>(value.value.clone().into())?;
+                Ok(Message::UpdateBeacon(msg))
+            }
             _ => Err(proto_messages::Error::DecodeGeneral(
                 "message type not recognized".into(),
             )),
@@ -0,0 +1,373 @@
+use database::{Database, PrefixDB};
+use gears::{
+    types::context::{Context, QueryContext},
+    x::params::{Keeper as ParamsKeeper, ParamsSubspaceKey},
+};
+use ibc_proto::protobuf::Protobuf;
+use proto_messages::Error;
+use serde::{Deserialize, Serialize};
+use store::{ImmutablePrefixStore, StoreKey};
+
+use crate::proto::tlcs::v1beta1::MsgUpdateParams;
+
+pub const DEFAULT_SECURITY_PARAM: u32 = 10;
+pub const DEFAULT_MIN_CONTRIBUTION_THRESHOLD: u32 = 2;
+pub const DEFAULT_MAX_MULTI_REQUESTS: u32 = 100;
+pub const DEFAULT_MIN_PUBKEY_LEAD_TIME: u32 = 30;
+pub const DEFAULT_BOND_DENOM: &str = "uatom";
+pub const DEFAULT_CONTRIBUTOR_BOND: u64 = 10;
+pub const DEFAULT_THRESHOLD_NUMERATOR: u32 = 2;
+pub const DEFAULT_THRESHOLD_DENOMINATOR: u32 = 3;
+pub const DEFAULT_REQUEST_FEE: u64 = 0;
+pub const DEFAULT_MIN_PARTICIPATION_RATIO: u32 = 0;
+pub const DEFAULT_RETENTION_BLOCKS: u64 = 0;
+pub const DEFAULT_MAX_PRUNES_PER_BLOCK: u32 = 100;
+pub const DEFAULT_MAX_KEY_DERIVATIONS_PER_BLOCK: u32 = 100;
+
+// Params subspace keys
+const KEY_SECURITY_PARAM: &[u8] = b"SecurityParam";
+const KEY_MIN_CONTRIBUTION_THRESHOLD: &[u8] = b"MinContributionThreshold";
+const KEY_MAX_MULTI_REQUESTS: &[u8] = b"MaxMultiRequests";
+const KEY_MIN_PUBKEY_LEAD_TIME: &[u8] = b"MinPubkeyLeadTime";
+const KEY_BOND_DENOM: &[u8] = b"BondDenom";
+const KEY_CONTRIBUTOR_BOND: &[u8] = b"ContributorBond";
+const KEY_THRESHOLD_NUMERATOR: &[u8] = b"ThresholdNumerator";
+const KEY_THRESHOLD_DENOMINATOR: &[u8] = b"ThresholdDenominator";
+const KEY_REQUEST_FEE: &[u8] = b"RequestFee";
+const KEY_MIN_PARTICIPATION_RATIO: &[u8] = b"MinParticipationRatio";
+const KEY_RETENTION_BLOCKS: &[u8] = b"RetentionBlocks";
+const KEY_MAX_PRUNES_PER_BLOCK: &[u8] = b"MaxPrunesPerBlock";
+const KEY_MAX_KEY_DERIVATIONS_PER_BLOCK: &[u8] = b"MaxKeyDerivationsPerBlock";
+
+#[derive(Serialize, Deserialize, Clone, prost::Message)]
+pub struct RawParams {
+    #[prost(uint32, tag = "5")]
+    pub security_param: u32,
+    #[prost(uint32, tag = "6")]
+    pub min_contribution_threshold: u32,
+    #[prost(uint32, tag = "7")]
+    pub max_multi_requests: u32,
+    #[prost(uint32, tag = "8")]
+    pub min_pubkey_lead_time: u32,
+    #[prost(string, tag = "9")]
+    pub bond_denom: String,
+    #[prost(uint64, tag = "10")]
+    pub contributor_bond: u64,
+    #[prost(uint32, tag = "11")]
+    pub threshold_numerator: u32,
+    #[prost(uint32, tag = "12")]
+    pub threshold_denominator: u32,
+    #[prost(uint64, tag = "13")]
+    pub request_fee: u64,
+    #[prost(uint32, tag = "14")]
+    pub min_participation_ratio: u32,
+    #[prost(uint64, tag = "15")]
+    pub retention_blocks: u64,
+    #[prost(uint32, tag = "16")]
+    pub max_prunes_per_block: u32,
+    #[prost(uint32, tag = "17")]
+    pub max_key_derivations_per_block: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Params {
+    pub security_param: u32,
+    /// Lower bound for the number of contributions needed to make a public key
+    pub min_contribution_threshold: u32,
+    /// Maximum number of keypairs that can be requested with a single MsgMultiNewProcess
+    pub max_multi_requests: u32,
+    /// Minimum number of seconds between the block time and the requested public key time
+    pub min_pubkey_lead_time: u32,
+    pub bond_denom: String,
+    /// Amount which is held by the module account while an address is a registered contributor
+    pub contributor_bond: u64,
+    /// The number of contributions needed to make a public key is this fraction of the active
+    /// contributors rounded down plus one, but never less than `min_contribution_threshold`.
+    /// Keypairs fail when that is more than the number of active contributors.
+    pub threshold_numerator: u32,
+    pub threshold_denominator: u32,
+    /// Fee in `bond_denom` which is escrowed for every keypair and paid to its contributors
+    pub request_fee: u64,
+    /// Contributors whose share of contributed windows among their recent ones, in parts per
+    /// thousand, falls below this are jailed. 0 disables jailing
+    pub min_participation_ratio: u32,
+    /// Number of blocks a released, failed or cancelled keypair and its contributions are kept
+    /// before the keypair is archived. 0 keeps them forever
+    pub retention_blocks: u64,
+    /// Maximum number of keypairs archived in a single block
+    pub max_prunes_per_block: u32,
+    /// Maximum number of public and private keys made in a block, the remaining work is carried
+    /// over to the next blocks
+    pub max_key_derivations_per_block: u32,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            security_param: DEFAULT_SECURITY_PARAM,
+            min_contribution_threshold: DEFAULT_MIN_CONTRIBUTION_THRESHOLD,
+            max_multi_requests: DEFAULT_MAX_MULTI_REQUESTS,
+            min_pubkey_lead_time: DEFAULT_MIN_PUBKEY_LEAD_TIME,
+            bond_denom: DEFAULT_BOND_DENOM.into(),
+            contributor_bond: DEFAULT_CONTRIBUTOR_BOND,
+            threshold_numerator: DEFAULT_THRESHOLD_NUMERATOR,
+            threshold_denominator: DEFAULT_THRESHOLD_DENOMINATOR,
+            request_fee: DEFAULT_REQUEST_FEE,
+            min_participation_ratio: DEFAULT_MIN_PARTICIPATION_RATIO,
+            retention_blocks: DEFAULT_RETENTION_BLOCKS,
+            max_prunes_per_block: DEFAULT_MAX_PRUNES_PER_BLOCK,
+            max_key_derivations_per_block: DEFAULT_MAX_KEY_DERIVATIONS_PER_BLOCK,
+        }
+    }
+}
+
+impl Params {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.security_param == 0 {
+            return Err("security param must be greater than zero".into());
+        }
+        if self.max_multi_requests == 0 {
+            return Err("max multi requests must be greater than zero".into());
+        }
+        if self.max_prunes_per_block == 0 {
+            return Err("max prunes per block must be greater than zero".into());
+        }
+        if self.max_key_derivations_per_block == 0 {
+            return Err("max key derivations per block must be greater than zero".into());
+        }
+        if self.bond_denom.is_empty() {
+            return Err("bond denom must not be empty".into());
+        }
+        if self.min_contribution_threshold == 0 {
+            return Err("min contribution threshold must be greater than zero".into());
+        }
+        if self.threshold_denominator == 0 {
+            return Err("threshold denominator must be greater than zero".into());
+        }
+        if self.threshold_numerator >= self.threshold_denominator {
+            return Err("threshold numerator must be less than the denominator".into());
+        }
+        if self.min_participation_ratio > 1000 {
+            return Err("min participation ratio must not be greater than 1000".into());
+        }
+
+        Ok(())
+    }
+
+    /// Applies the fields which are set in the update
+    pub fn update(&mut self, msg: &MsgUpdateParams) {
+        if let Some(security_param) = msg.security_param {
+            self.security_param = security_param;
+        }
+        if let Some(min_contribution_threshold) = msg.min_contribution_threshold {
+            self.min_contribution_threshold = min_contribution_threshold;
+        }
+        if let Some(max_multi_requests) = msg.max_multi_requests {
+            self.max_multi_requests = max_multi_requests;
+        }
+        if let Some(min_pubkey_lead_time) = msg.min_pubkey_lead_time {
+            self.min_pubkey_lead_time = min_pubkey_lead_time;
+        }
+        if let Some(bond_denom) = &msg.bond_denom {
+            self.bond_denom = bond_denom.clone();
+        }
+        if let Some(contributor_bond) = msg.contributor_bond {
+            self.contributor_bond = contributor_bond;
+        }
+        if let Some(threshold_numerator) = msg.threshold_numerator {
+            self.threshold_numerator = threshold_numerator;
+        }
+        if let Some(threshold_denominator) = msg.threshold_denominator {
+            self.threshold_denominator = threshold_denominator;
+        }
+        if let Some(request_fee) = msg.request_fee {
+            self.request_fee = request_fee;
+        }
+        if let Some(min_participation_ratio) = msg.min_participation_ratio {
+            self.min_participation_ratio = min_participation_ratio;
+        }
+        if let Some(retention_blocks) = msg.retention_blocks {
+            self.retention_blocks = retention_blocks;
+        }
+        if let Some(max_prunes_per_block) = msg.max_prunes_per_block {
+            self.max_prunes_per_block = max_prunes_per_block;
+        }
+        if let Some(max_key_derivations_per_block) = msg.max_key_derivations_per_block {
+            self.max_key_derivations_per_block = max_key_derivations_per_block;
+        }
+    }
+}
+
+impl TryFrom<RawParams> for Params {
+    type Error = Error;
+
+    fn try_from(raw: RawParams) -> Result<Self, Self::Error> {
+        Ok(Params {
+            security_param: raw.security_param,
+            min_contribution_threshold: raw.min_contribution_threshold,
+            max_multi_requests: raw.max_multi_requests,
+            min_pubkey_lead_time: raw.min_pubkey_lead_time,
+            bond_denom: raw.bond_denom,
+            contributor_bond: raw.contributor_bond,
+            threshold_numerator: raw.threshold_numerator,
+            threshold_denominator: raw.threshold_denominator,
+            request_fee: raw.request_fee,
+            min_participation_ratio: raw.min_participation_ratio,
+            retention_blocks: raw.retention_blocks,
+            max_prunes_per_block: raw.max_prunes_per_block,
+            max_key_derivations_per_block: raw.max_key_derivations_per_block,
+        })
+    }
+}
+
+impl From<Params> for RawParams {
+    fn from(params: Params) -> RawParams {
+        RawParams {
+            security_param: params.security_param,
+            min_contribution_threshold: params.min_contribution_threshold,
+            max_multi_requests: params.max_multi_requests,
+            min_pubkey_lead_time: params.min_pubkey_lead_time,
+            bond_denom: params.bond_denom,
+            contributor_bond: params.contributor_bond,
+            threshold_numerator: params.threshold_numerator,
+            threshold_denominator: params.threshold_denominator,
+            request_fee: params.request_fee,
+            min_participation_ratio: params.min_participation_ratio,
+            retention_blocks: params.retention_blocks,
+            max_prunes_per_block: params.max_prunes_per_block,
+            max_key_derivations_per_block: params.max_key_derivations_per_block,
+        }
+    }
+}
+
+impl Protobuf<RawParams> for Params {}
+
+#[derive(Debug, Clone)]
+pub struct TimelockParamsKeeper<SK: StoreKey, PSK: ParamsSubspaceKey> {
+    pub params_keeper: ParamsKeeper<SK, PSK>,
+    pub params_subspace_key: PSK,
+    // The params keeper only exposes subspaces through a tx or init context, queries read
+    // the subspace directly from the params store
+    pub params_store_key: SK,
+}
+
+impl<SK: StoreKey, PSK: ParamsSubspaceKey> TimelockParamsKeeper<SK, PSK> {
+    pub fn get<DB: Database>(&self, ctx: &Context<DB, SK>) -> Params {
+        let store = self
+            .params_keeper
+            .get_raw_subspace(ctx, &self.params_subspace_key);
+
+        parse_params(store)
+    }
+
+    pub fn query<DB: Database>(&self, ctx: &QueryContext<DB, SK>) -> Params {
+        let store = ctx
+            .get_kv_store(&self.params_store_key)
+            .get_immutable_prefix_store(self.params_subspace_key.name().as_bytes().to_vec());
+
+        parse_params(store)
+    }
+
+    /// Whether the params have been written to the subspace. The first release of the module
+    /// had no params, so the store migration must set them before anything reads them.
+    pub fn is_set<DB: Database>(&self, ctx: &Context<DB, SK>) -> bool {
+        ctx.get_kv_store(&self.params_store_key)
+            .get_immutable_prefix_store(self.params_subspace_key.name().as_bytes().to_vec())
+            .get(KEY_SECURITY_PARAM)
+            .is_some()
+    }
+
+    pub fn set<DB: Database>(&self, ctx: &mut Context<DB, SK>, params: Params) {
+        let mut store = self
+            .params_keeper
+            .get_mutable_raw_subspace(ctx, &self.params_subspace_key);
+
+        store.set(
+            KEY_SECURITY_PARAM.into(),
+            params.security_param.to_string().into_bytes(),
+        );
+        store.set(
+            KEY_MIN_CONTRIBUTION_THRESHOLD.into(),
+            params.min_contribution_threshold.to_string().into_bytes(),
+        );
+        store.set(
+            KEY_MAX_MULTI_REQUESTS.into(),
+            params.max_multi_requests.to_string().into_bytes(),
+        );
+        store.set(
+            KEY_MIN_PUBKEY_LEAD_TIME.into(),
+            params.min_pubkey_lead_time.to_string().into_bytes(),
+        );
+        store.set(KEY_BOND_DENOM.into(), params.bond_denom.into_bytes());
+        store.set(
+            KEY_CONTRIBUTOR_BOND.into(),
+            params.contributor_bond.to_string().into_bytes(),
+        );
+        store.set(
+            KEY_THRESHOLD_NUMERATOR.into(),
+            params.threshold_numerator.to_string().into_bytes(),
+        );
+        store.set(
+            KEY_THRESHOLD_DENOMINATOR.into(),
+            params.threshold_denominator.to_string().into_bytes(),
+        );
+        store.set(
+            KEY_REQUEST_FEE.into(),
+            params.request_fee.to_string().into_bytes(),
+        );
+        store.set(
+            KEY_MIN_PARTICIPATION_RATIO.into(),
+            params.min_participation_ratio.to_string().into_bytes(),
+        );
+        store.set(
+            KEY_RETENTION_BLOCKS.into(),
+            params.retention_blocks.to_string().into_bytes(),
+        );
+        store.set(
+            KEY_MAX_PRUNES_PER_BLOCK.into(),
+            params.max_prunes_per_block.to_string().into_bytes(),
+        );
+        store.set(
+            KEY_MAX_KEY_DERIVATIONS_PER_BLOCK.into(),
+            params
+                .max_key_derivations_per_block
+                .to_string()
+                .into_bytes(),
+        );
+    }
+}
+
+fn get_string<DB: Database>(store: &ImmutablePrefixStore<PrefixDB<DB>>, key: &[u8]) -> String {
+    String::from_utf8(store.get(key).expect("key should be set in kv store"))
+        .expect("should be valid utf-8")
+}
+
+fn get_u32<DB: Database>(store: &ImmutablePrefixStore<PrefixDB<DB>>, key: &[u8]) -> u32 {
+    get_string(store, key)
+        .parse()
+        .expect("should be a valid u32")
+}
+
+fn get_u64<DB: Database>(store: &ImmutablePrefixStore<PrefixDB<DB>>, key: &[u8]) -> u64 {
+    get_string(store, key)
+        .parse()
+        .expect("should be a valid u64")
+}
+
+fn parse_params<DB: Database>(store: ImmutablePrefixStore<PrefixDB<DB>>) -> Params {
+    Params {
+        security_param: get_u32(&store, KEY_SECURITY_PARAM),
+        min_contribution_threshold: get_u32(&store, KEY_MIN_CONTRIBUTION_THRESHOLD),
+        max_multi_requests: get_u32(&store, KEY_MAX_MULTI_REQUESTS),
+        min_pubkey_lead_time: get_u32(&store, KEY_MIN_PUBKEY_LEAD_TIME),
+        bond_denom: get_string(&store, KEY_BOND_DENOM),
+        contributor_bond: get_u64(&store, KEY_CONTRIBUTOR_BOND),
+        threshold_numerator: get_u32(&store, KEY_THRESHOLD_NUMERATOR),
+        threshold_denominator: get_u32(&store, KEY_THRESHOLD_DENOMINATOR),
+        request_fee: get_u64(&store, KEY_REQUEST_FEE),
+        min_participation_ratio: get_u32(&store, KEY_MIN_PARTICIPATION_RATIO),
+        retention_blocks: get_u64(&store, KEY_RETENTION_BLOCKS),
+        max_prunes_per_block: get_u32(&store, KEY_MAX_PRUNES_PER_BLOCK),
+        max_key_derivations_per_block: get_u32(&store, KEY_MAX_KEY_DERIVATIONS_PER_BLOCK),
+    }
+}
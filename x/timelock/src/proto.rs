@@ -26,6 +26,12 @@ pub mod tlcs {
             pub scheme: u32,
             #[prost(int64, tag = "4")]
             pub pubkey_time: i64,
+            /// Share the canonical keypair of the round and scheme instead of opening a new one
+            #[prost(bool, tag = "5")]
+            pub canonical: bool,
+            /// Registered beacon whose signature of the round releases the private key
+            #[prost(uint32, tag = "6")]
+            pub beacon_id: u32,
         }
 
         #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -34,6 +40,8 @@ pub mod tlcs {
             pub round: u64,
             pub scheme: u32,
             pub pubkey_time: i64,
+            pub canonical: bool,
+            pub beacon_id: u32,
         }
 
         impl TryFrom<RawMsgNewProcess> for MsgNewProcess {
@@ -48,6 +56,8 @@ pub mod tlcs {
                     round: raw.round,
                     scheme: raw.scheme,
                     pubkey_time: raw.pubkey_time,
+                    canonical: raw.canonical,
+                    beacon_id: raw.beacon_id,
                 })
             }
         }
@@ -59,6 +69,8 @@ pub mod tlcs {
                     round: msg.round,
                     scheme: msg.scheme,
                     pubkey_time: msg.pubkey_time,
+                    canonical: msg.canonical,
+                    beacon_id: msg.beacon_id,
                 }
             }
         }
@@ -97,6 +109,8 @@ pub mod tlcs {
             pub schemes: ::prost::alloc::vec::Vec<u32>,
             #[prost(int64, tag = "6")]
             pub pubkey_time: i64,
+            #[prost(uint32, tag = "7")]
+            pub beacon_id: u32,
         }
 
         #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -107,6 +121,7 @@ pub mod tlcs {
             pub roundstep: u32,
             pub schemes: Vec<u32>,
             pub pubkey_time: i64,
+            pub beacon_id: u32,
         }
 
         impl TryFrom<RawMsgMultiNewProcess> for MsgMultiNewProcess {
@@ -123,6 +138,7 @@ pub mod tlcs {
                     roundstep: raw.roundstep,
                     schemes: raw.schemes,
                     pubkey_time: raw.pubkey_time,
+                    beacon_id: raw.beacon_id,
                 })
             }
         }
@@ -136,6 +152,7 @@ pub mod tlcs {
                     roundstep: msg.roundstep,
                     schemes: msg.schemes,
                     pubkey_time: msg.pubkey_time,
+                    beacon_id: msg.beacon_id,
                 }
             }
         }
@@ -174,6 +191,12 @@ pub mod tlcs {
             pub id: u32,
             #[prost(bytes, tag = "5")]
             pub data: Vec<u8>,
+            /// Block height at which the contribution was first stored. Set by the chain.
+            #[prost(uint64, tag = "6")]
+            pub submitted_height: u64,
+            /// Block time at which the contribution was first stored. Set by the chain.
+            #[prost(int64, tag = "7")]
+            pub submitted_time: i64,
         }
 
         #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -210,6 +233,8 @@ pub mod tlcs {
                     scheme: msg.scheme,
                     id: msg.id,
                     data: msg.data,
+                    submitted_height: 0,
+                    submitted_time: 0,
                 }
             }
         }
@@ -230,6 +255,8 @@ pub mod tlcs {
         pub struct QueryAllContributionsResponse {
             #[prost(message, repeated, tag = "1")]
             pub contributions: Vec<RawMsgContribution>,
+            #[prost(message, optional, tag = "2")]
+            pub pagination: Option<PageResponse>,
         }
 
         impl Protobuf<QueryAllContributionsResponse> for QueryAllContributionsResponse {}
@@ -238,6 +265,101 @@ pub mod tlcs {
         // KeyPair Section
         /////////////////////////////////////////////////////////////////////////////////////
 
+        /// Lifecycle of a keypair. A keypair starts as `Requested` and ends as `Released`,
+        /// `Failed` or `Cancelled`.
+        #[derive(
+            Serialize,
+            Deserialize,
+            Clone,
+            Copy,
+            Debug,
+            PartialEq,
+            Eq,
+            Hash,
+            PartialOrd,
+            Ord,
+            prost::Enumeration,
+        )]
+        #[serde(rename_all = "snake_case")]
+        #[repr(i32)]
+        pub enum KeyPairStatus {
+            /// Waiting for the first contribution
+            Requested = 0,
+            /// At least one contribution was received, more are accepted until `pubkey_time`
+            Collecting = 1,
+            /// The public key was made, the private key is made once the LOE data of the round
+            /// is available
+            PublicKeyReady = 2,
+            /// The LOE round was reached but its data wasn't submitted yet
+            AwaitingBeacon = 3,
+            /// The private key was made
+            Released = 4,
+            /// The public key time passed without enough contributions
+            Failed = 5,
+            /// The request was withdrawn before its public key was made
+            Cancelled = 6,
+        }
+
+        impl KeyPairStatus {
+            /// Returns whether a keypair in this status may move to `next`
+            pub fn can_transition_to(&self, next: KeyPairStatus) -> bool {
+                use KeyPairStatus::*;
+
+                matches!(
+                    (self, next),
+                    (Requested, Collecting)
+                        | (Requested, Failed)
+                        | (Requested, Cancelled)
+                        | (Collecting, PublicKeyReady)
+                        | (Collecting, Failed)
+                        | (Collecting, Cancelled)
+                        | (PublicKeyReady, AwaitingBeacon)
+                        | (PublicKeyReady, Released)
+                        | (AwaitingBeacon, Released)
+                )
+            }
+
+            /// Whether the keypair is done, no status follows this one
+            pub fn is_final(&self) -> bool {
+                matches!(
+                    self,
+                    KeyPairStatus::Released | KeyPairStatus::Failed | KeyPairStatus::Cancelled
+                )
+            }
+        }
+
+        // Statuses are shown by name in JSON so clients don't need to know the numbering
+        mod key_pair_status {
+            use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+            use super::KeyPairStatus;
+
+            pub fn serialize<S: Serializer>(
+                status: &i32,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                KeyPairStatus::from_i32(*status)
+                    .ok_or_else(|| serde::ser::Error::custom("invalid keypair status"))?
+                    .serialize(serializer)
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<i32, D::Error> {
+                Ok(KeyPairStatus::deserialize(deserializer)? as i32)
+            }
+        }
+
+        /// A status a keypair entered and the height of the block in which it happened
+        #[derive(Serialize, Deserialize, Clone, PartialEq, Message)]
+        pub struct RawKeyPairStatusChange {
+            #[prost(enumeration = "KeyPairStatus", tag = "1")]
+            #[serde(with = "key_pair_status")]
+            pub status: i32,
+            #[prost(uint64, tag = "2")]
+            pub height: u64,
+        }
+
         #[derive(Serialize, Deserialize, Clone, Message)]
         pub struct RawMsgKeyPair {
             #[prost(uint64, tag = "1")]
@@ -252,6 +374,29 @@ pub mod tlcs {
             pub public_key: String,
             #[prost(string, tag = "6")]
             pub private_key: String,
+            /// Number of contributions which were required when the public key was made
+            #[prost(uint32, tag = "7")]
+            pub contribution_threshold: u32,
+            /// Block time at which the keypair was requested. Contributions are accepted from
+            /// this time until `pubkey_time`.
+            #[prost(int64, tag = "8")]
+            pub contribution_start: i64,
+            /// Canonical keypairs are shared by every canonical request for the round and scheme
+            #[prost(bool, tag = "9")]
+            pub canonical: bool,
+            #[prost(enumeration = "KeyPairStatus", tag = "11")]
+            #[serde(with = "key_pair_status")]
+            pub status: i32,
+            /// Every status the keypair entered, oldest first
+            #[prost(message, repeated, tag = "12")]
+            pub status_history: Vec<RawKeyPairStatusChange>,
+            /// Address which requested the keypair. Empty for keypairs requested before the
+            /// requester was recorded.
+            #[prost(string, tag = "13")]
+            pub owner: String,
+            /// Beacon whose round signature releases the private key
+            #[prost(uint32, tag = "14")]
+            pub beacon_id: u32,
         }
 
         #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -262,6 +407,13 @@ pub mod tlcs {
             pub pubkey_time: i64,
             pub public_key: String,
             pub private_key: String,
+            pub contribution_threshold: u32,
+            pub contribution_start: i64,
+            pub canonical: bool,
+            pub status: KeyPairStatus,
+            pub status_history: Vec<RawKeyPairStatusChange>,
+            pub owner: String,
+            pub beacon_id: u32,
         }
 
         impl TryFrom<RawMsgKeyPair> for MsgKeyPair {
@@ -275,6 +427,14 @@ pub mod tlcs {
                     pubkey_time: raw.pubkey_time,
                     public_key: raw.public_key,
                     private_key: raw.private_key,
+                    contribution_threshold: raw.contribution_threshold,
+                    contribution_start: raw.contribution_start,
+                    canonical: raw.canonical,
+                    status: KeyPairStatus::from_i32(raw.status)
+                        .ok_or_else(|| Error::DecodeGeneral("invalid keypair status".into()))?,
+                    status_history: raw.status_history,
+                    owner: raw.owner,
+                    beacon_id: raw.beacon_id,
                 })
             }
         }
@@ -288,6 +448,13 @@ pub mod tlcs {
                     pubkey_time: msg.pubkey_time,
                     public_key: msg.public_key,
                     private_key: msg.private_key,
+                    contribution_threshold: msg.contribution_threshold,
+                    contribution_start: msg.contribution_start,
+                    canonical: msg.canonical,
+                    status: msg.status as i32,
+                    status_history: msg.status_history,
+                    owner: msg.owner,
+                    beacon_id: msg.beacon_id,
                 }
             }
         }
@@ -308,10 +475,127 @@ pub mod tlcs {
         pub struct QueryAllKeyPairsResponse {
             #[prost(message, repeated, tag = "1")]
             pub keypairs: Vec<RawMsgKeyPair>,
+            #[prost(message, optional, tag = "2")]
+            pub pagination: Option<PageResponse>,
         }
 
         impl Protobuf<QueryAllKeyPairsResponse> for QueryAllKeyPairsResponse {}
 
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct QueryKeyPairResponse {
+            #[prost(message, optional, tag = "1")]
+            pub keypair: Option<RawMsgKeyPair>,
+        }
+
+        impl Protobuf<QueryKeyPairResponse> for QueryKeyPairResponse {}
+
+        /// What is kept of a keypair once its retention period ended
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct RawArchivedKeyPair {
+            #[prost(uint64, tag = "1")]
+            pub round: u64,
+            #[prost(uint32, tag = "2")]
+            pub scheme: u32,
+            #[prost(uint32, tag = "3")]
+            pub id: u32,
+            #[prost(string, tag = "4")]
+            pub public_key: String,
+            #[prost(string, tag = "5")]
+            pub private_key: String,
+            #[prost(uint64, tag = "6")]
+            pub archived_height: u64,
+            /// Final status of the keypair, the keys are empty unless it was released
+            #[prost(enumeration = "KeyPairStatus", tag = "7")]
+            #[serde(with = "key_pair_status")]
+            pub status: i32,
+        }
+
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct QueryArchivedKeyPairsResponse {
+            #[prost(message, repeated, tag = "1")]
+            pub keypairs: Vec<RawArchivedKeyPair>,
+            #[prost(message, optional, tag = "2")]
+            pub pagination: Option<PageResponse>,
+        }
+
+        impl Protobuf<QueryArchivedKeyPairsResponse> for QueryArchivedKeyPairsResponse {}
+
+        /// Totals of the records removed by pruning
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct QueryPruneStatsResponse {
+            #[prost(uint64, tag = "1")]
+            pub archived_keypairs: u64,
+            #[prost(uint64, tag = "2")]
+            pub pruned_contributions: u64,
+            #[prost(uint64, tag = "3")]
+            pub pruned_loe_data: u64,
+            #[prost(uint64, tag = "4")]
+            pub last_pruned_height: u64,
+            /// Released, failed and cancelled keypairs which are waiting for their retention
+            /// period to end
+            #[prost(uint64, tag = "5")]
+            pub pending: u64,
+        }
+
+        impl Protobuf<QueryPruneStatsResponse> for QueryPruneStatsResponse {}
+
+        /// Key derivations which were due but didn't fit in the block budget
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct QueryPendingWorkResponse {
+            /// Keypairs whose public key time has passed but which weren't processed yet
+            #[prost(uint64, tag = "1")]
+            pub public_keys: u64,
+            /// Keypairs whose LOE data is available but which weren't processed yet
+            #[prost(uint64, tag = "2")]
+            pub private_keys: u64,
+            /// Height of the block which carried the work over
+            #[prost(uint64, tag = "3")]
+            pub height: u64,
+        }
+
+        impl Protobuf<QueryPendingWorkResponse> for QueryPendingWorkResponse {}
+
+        /////////////////////////////////////////////////////////////////////////////////////
+        // Pagination Section
+        /////////////////////////////////////////////////////////////////////////////////////
+
+        // Page keys are shown as hex in JSON so they can be passed back in a query string
+        mod hex_key {
+            use serde::{Deserialize, Deserializer, Serializer};
+
+            pub fn serialize<S: Serializer>(key: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&hex::encode(key))
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Vec<u8>, D::Error> {
+                let key = String::deserialize(deserializer)?;
+                hex::decode(key).map_err(serde::de::Error::custom)
+            }
+        }
+
+        /// Selects a page of a query result. A page starts at `key` if it's set, `offset` rows
+        /// are skipped from there. A zero `limit` uses the default page size.
+        #[derive(Serialize, Deserialize, Clone, PartialEq, Message)]
+        pub struct PageRequest {
+            #[prost(bytes = "vec", tag = "1")]
+            #[serde(with = "hex_key")]
+            pub key: Vec<u8>,
+            #[prost(uint64, tag = "2")]
+            pub offset: u64,
+            #[prost(uint64, tag = "3")]
+            pub limit: u64,
+        }
+
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct PageResponse {
+            /// Key of the first row of the next page, empty on the last page
+            #[prost(bytes = "vec", tag = "1")]
+            #[serde(with = "hex_key")]
+            pub next_key: Vec<u8>,
+        }
+
         /////////////////////////////////////////////////////////////////////////////////////
         // Round and Scheme Query message
         /////////////////////////////////////////////////////////////////////////////////////
@@ -321,24 +605,33 @@ pub mod tlcs {
         pub struct RawQueryRoundRequest {
             #[prost(uint64, tag = "1")]
             pub round: u64,
+            #[prost(message, optional, tag = "2")]
+            pub pagination: Option<PageRequest>,
         }
 
         #[derive(Clone, PartialEq, Serialize, Deserialize)]
         pub struct QueryRoundRequest {
             pub round: u64,
+            pub pagination: Option<PageRequest>,
         }
 
         impl TryFrom<RawQueryRoundRequest> for QueryRoundRequest {
             type Error = Error;
 
             fn try_from(raw: RawQueryRoundRequest) -> Result<Self, Self::Error> {
-                Ok(QueryRoundRequest { round: raw.round })
+                Ok(QueryRoundRequest {
+                    round: raw.round,
+                    pagination: raw.pagination,
+                })
             }
         }
 
         impl From<QueryRoundRequest> for RawQueryRoundRequest {
             fn from(query: QueryRoundRequest) -> RawQueryRoundRequest {
-                RawQueryRoundRequest { round: query.round }
+                RawQueryRoundRequest {
+                    round: query.round,
+                    pagination: query.pagination,
+                }
             }
         }
 
@@ -351,12 +644,15 @@ pub mod tlcs {
             pub round: u64,
             #[prost(uint32, tag = "2")]
             pub scheme: u32,
+            #[prost(message, optional, tag = "3")]
+            pub pagination: Option<PageRequest>,
         }
 
         #[derive(Clone, PartialEq, Serialize, Deserialize)]
         pub struct QueryRoundSchemeRequest {
             pub round: u64,
             pub scheme: u32,
+            pub pagination: Option<PageRequest>,
         }
 
         impl TryFrom<RawQueryRoundSchemeRequest> for QueryRoundSchemeRequest {
@@ -366,7 +662,7 @@ pub mod tlcs {
                 Ok(QueryRoundSchemeRequest {
                     round: raw.round,
                     scheme: raw.scheme,
-                    //pagination: raw.pagination,
+                    pagination: raw.pagination,
                 })
             }
         }
@@ -376,41 +672,181 @@ pub mod tlcs {
                 RawQueryRoundSchemeRequest {
                     round: query.round,
                     scheme: query.scheme,
-                    //pagination: query.pagination,
+                    pagination: query.pagination,
                 }
             }
         }
 
         impl Protobuf<RawQueryRoundSchemeRequest> for QueryRoundSchemeRequest {}
 
+        /// QueryKeyPairRequest is the request type for the Query/AllContributionsByKeyPair RPC method.
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct RawQueryKeyPairRequest {
+            #[prost(uint64, tag = "1")]
+            pub round: u64,
+            #[prost(uint32, tag = "2")]
+            pub scheme: u32,
+            #[prost(uint32, tag = "3")]
+            pub id: u32,
+            #[prost(message, optional, tag = "4")]
+            pub pagination: Option<PageRequest>,
+        }
+
+        #[derive(Clone, PartialEq, Serialize, Deserialize)]
+        pub struct QueryKeyPairRequest {
+            pub round: u64,
+            pub scheme: u32,
+            pub id: u32,
+            pub pagination: Option<PageRequest>,
+        }
+
+        impl TryFrom<RawQueryKeyPairRequest> for QueryKeyPairRequest {
+            type Error = Error;
+
+            fn try_from(raw: RawQueryKeyPairRequest) -> Result<Self, Self::Error> {
+                Ok(QueryKeyPairRequest {
+                    round: raw.round,
+                    scheme: raw.scheme,
+                    id: raw.id,
+                    pagination: raw.pagination,
+                })
+            }
+        }
+
+        impl From<QueryKeyPairRequest> for RawQueryKeyPairRequest {
+            fn from(query: QueryKeyPairRequest) -> RawQueryKeyPairRequest {
+                RawQueryKeyPairRequest {
+                    round: query.round,
+                    scheme: query.scheme,
+                    id: query.id,
+                    pagination: query.pagination,
+                }
+            }
+        }
+
+        impl Protobuf<RawQueryKeyPairRequest> for QueryKeyPairRequest {}
+
         /// QueryTimeRequest is the request type for the Query/KeyPair RPC method.
         #[derive(Serialize, Deserialize, Clone, Message)]
         pub struct RawQueryTimeRequest {
             #[prost(int64, tag = "1")]
             pub time: i64,
+            #[prost(message, optional, tag = "2")]
+            pub pagination: Option<PageRequest>,
         }
 
         #[derive(Clone, PartialEq, Serialize, Deserialize)]
         pub struct QueryTimeRequest {
             pub time: i64,
+            pub pagination: Option<PageRequest>,
         }
 
         impl TryFrom<RawQueryTimeRequest> for QueryTimeRequest {
             type Error = Error;
 
             fn try_from(raw: RawQueryTimeRequest) -> Result<Self, Self::Error> {
-                Ok(QueryTimeRequest { time: raw.time })
+                Ok(QueryTimeRequest {
+                    time: raw.time,
+                    pagination: raw.pagination,
+                })
             }
         }
 
         impl From<QueryTimeRequest> for RawQueryTimeRequest {
             fn from(query: QueryTimeRequest) -> RawQueryTimeRequest {
-                RawQueryTimeRequest { time: query.time }
+                RawQueryTimeRequest {
+                    time: query.time,
+                    pagination: query.pagination,
+                }
             }
         }
 
         impl Protobuf<RawQueryTimeRequest> for QueryTimeRequest {}
 
+        /// QueryRoundRangeRequest is the request type for the Query/KeypairsByRoundRange RPC method.
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct RawQueryRoundRangeRequest {
+            #[prost(uint64, tag = "1")]
+            pub from: u64,
+            #[prost(uint64, tag = "2")]
+            pub to: u64,
+            #[prost(message, optional, tag = "3")]
+            pub pagination: Option<PageRequest>,
+        }
+
+        #[derive(Clone, PartialEq, Serialize, Deserialize)]
+        pub struct QueryRoundRangeRequest {
+            pub from: u64,
+            pub to: u64,
+            pub pagination: Option<PageRequest>,
+        }
+
+        impl TryFrom<RawQueryRoundRangeRequest> for QueryRoundRangeRequest {
+            type Error = Error;
+
+            fn try_from(raw: RawQueryRoundRangeRequest) -> Result<Self, Self::Error> {
+                Ok(QueryRoundRangeRequest {
+                    from: raw.from,
+                    to: raw.to,
+                    pagination: raw.pagination,
+                })
+            }
+        }
+
+        impl From<QueryRoundRangeRequest> for RawQueryRoundRangeRequest {
+            fn from(query: QueryRoundRangeRequest) -> RawQueryRoundRangeRequest {
+                RawQueryRoundRangeRequest {
+                    from: query.from,
+                    to: query.to,
+                    pagination: query.pagination,
+                }
+            }
+        }
+
+        impl Protobuf<RawQueryRoundRangeRequest> for QueryRoundRangeRequest {}
+
+        /// QueryTimeRangeRequest is the request type for the Query/KeypairsByTimeRange RPC method.
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct RawQueryTimeRangeRequest {
+            #[prost(int64, tag = "1")]
+            pub from_unix: i64,
+            #[prost(int64, tag = "2")]
+            pub to_unix: i64,
+            #[prost(message, optional, tag = "3")]
+            pub pagination: Option<PageRequest>,
+        }
+
+        #[derive(Clone, PartialEq, Serialize, Deserialize)]
+        pub struct QueryTimeRangeRequest {
+            pub from_unix: i64,
+            pub to_unix: i64,
+            pub pagination: Option<PageRequest>,
+        }
+
+        impl TryFrom<RawQueryTimeRangeRequest> for QueryTimeRangeRequest {
+            type Error = Error;
+
+            fn try_from(raw: RawQueryTimeRangeRequest) -> Result<Self, Self::Error> {
+                Ok(QueryTimeRangeRequest {
+                    from_unix: raw.from_unix,
+                    to_unix: raw.to_unix,
+                    pagination: raw.pagination,
+                })
+            }
+        }
+
+        impl From<QueryTimeRangeRequest> for RawQueryTimeRangeRequest {
+            fn from(query: QueryTimeRangeRequest) -> RawQueryTimeRangeRequest {
+                RawQueryTimeRangeRequest {
+                    from_unix: query.from_unix,
+                    to_unix: query.to_unix,
+                    pagination: query.pagination,
+                }
+            }
+        }
+
+        impl Protobuf<RawQueryTimeRangeRequest> for QueryTimeRangeRequest {}
+
         /////////////////////////////////////////////////////////////////////////////////////
         // LOE Data input structs
         /////////////////////////////////////////////////////////////////////////////////////
@@ -423,6 +859,12 @@ pub mod tlcs {
             pub round: u64,
             #[prost(string, tag = "3")]
             pub signature: String,
+            /// Signature of the previous round, only needed for chained beacons
+            #[prost(string, tag = "4")]
+            pub previous_signature: String,
+            /// Registered beacon which published the signature
+            #[prost(uint32, tag = "5")]
+            pub beacon_id: u32,
         }
 
         #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -430,6 +872,8 @@ pub mod tlcs {
             pub address: AccAddress,
             pub round: u64,
             pub signature: String,
+            pub previous_signature: String,
+            pub beacon_id: u32,
         }
 
         impl TryFrom<RawMsgLoeData> for MsgLoeData {
@@ -443,6 +887,8 @@ pub mod tlcs {
                     address,
                     round: raw.round,
                     signature: raw.signature,
+                    previous_signature: raw.previous_signature,
+                    beacon_id: raw.beacon_id,
                 })
             }
         }
@@ -453,6 +899,8 @@ pub mod tlcs {
                     address: msg.address.into(),
                     round: msg.round,
                     signature: msg.signature,
+                    previous_signature: msg.previous_signature,
+                    beacon_id: msg.beacon_id,
                 }
             }
         }
@@ -473,8 +921,506 @@ pub mod tlcs {
         pub struct QueryAllLoeDataResponse {
             #[prost(message, repeated, tag = "1")]
             pub randomnesses: Vec<RawMsgLoeData>,
+            #[prost(message, optional, tag = "2")]
+            pub pagination: Option<PageResponse>,
         }
 
         impl Protobuf<QueryAllLoeDataResponse> for QueryAllLoeDataResponse {}
+
+        /// Keypairs waiting for LOE data, with the beacons which publish it
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct QueryLoeDataNeededResponse {
+            #[prost(message, repeated, tag = "1")]
+            pub keypairs: Vec<RawMsgKeyPair>,
+            #[prost(message, repeated, tag = "2")]
+            pub beacons: Vec<RawBeacon>,
+            #[prost(message, optional, tag = "3")]
+            pub pagination: Option<PageResponse>,
+        }
+
+        impl Protobuf<QueryLoeDataNeededResponse> for QueryLoeDataNeededResponse {}
+
+        /////////////////////////////////////////////////////////////////////////////////////
+        // Contributor Registry Section
+        /////////////////////////////////////////////////////////////////////////////////////
+
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct RawContributor {
+            #[prost(string, tag = "1")]
+            pub address: String,
+            /// Bond held by the timelock module account, refunded on deregistration unless the
+            /// contributor is jailed
+            #[prost(uint64, tag = "2")]
+            pub bond: u64,
+            #[prost(string, tag = "3")]
+            pub bond_denom: String,
+            #[prost(uint64, tag = "4")]
+            pub registered_height: u64,
+            /// Block time of the registration. Only windows which opened after it are counted.
+            #[prost(int64, tag = "5")]
+            pub registered_time: i64,
+            /// Jailed contributors can't contribute and don't count towards the threshold
+            #[prost(bool, tag = "8")]
+            pub jailed: bool,
+        }
+
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct QueryContributorsResponse {
+            #[prost(message, repeated, tag = "1")]
+            pub contributors: Vec<RawContributor>,
+            #[prost(message, optional, tag = "2")]
+            pub pagination: Option<PageResponse>,
+        }
+
+        impl Protobuf<QueryContributorsResponse> for QueryContributorsResponse {}
+
+        /// Contribution windows counted for an address. The counts are kept when the address
+        /// deregisters, so registering again doesn't reset them.
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct RawContributorStats {
+            #[prost(string, tag = "1")]
+            pub address: String,
+            #[prost(uint64, tag = "2")]
+            pub contributed_count: u64,
+            #[prost(uint64, tag = "3")]
+            pub missed_count: u64,
+            /// Whether the address contributed in each of the last windows since it registered,
+            /// oldest first. Jailing only looks at these, they are cleared on registration.
+            #[prost(bool, repeated, tag = "4")]
+            pub recent_windows: Vec<bool>,
+        }
+
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct QueryContributorStatsResponse {
+            #[prost(message, optional, tag = "1")]
+            pub contributor: Option<RawContributor>,
+            /// Contributed windows in parts per thousand of all counted windows
+            #[prost(uint32, tag = "2")]
+            pub uptime: u32,
+            #[prost(message, optional, tag = "3")]
+            pub stats: Option<RawContributorStats>,
+        }
+
+        impl Protobuf<QueryContributorStatsResponse> for QueryContributorStatsResponse {}
+
+        /// QueryAddressRequest is the request type for the Query/ContributorStats RPC method.
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct RawQueryAddressRequest {
+            #[prost(string, tag = "1")]
+            pub address: String,
+            #[prost(message, optional, tag = "2")]
+            pub pagination: Option<PageRequest>,
+        }
+
+        #[derive(Clone, PartialEq, Serialize, Deserialize)]
+        pub struct QueryAddressRequest {
+            pub address: AccAddress,
+            pub pagination: Option<PageRequest>,
+        }
+
+        impl TryFrom<RawQueryAddressRequest> for QueryAddressRequest {
+            type Error = Error;
+
+            fn try_from(raw: RawQueryAddressRequest) -> Result<Self, Self::Error> {
+                let address = AccAddress::from_bech32(&raw.address)
+                    .map_err(|e| Error::DecodeAddress(e.to_string()))?;
+
+                Ok(QueryAddressRequest {
+                    address,
+                    pagination: raw.pagination,
+                })
+            }
+        }
+
+        impl From<QueryAddressRequest> for RawQueryAddressRequest {
+            fn from(query: QueryAddressRequest) -> RawQueryAddressRequest {
+                RawQueryAddressRequest {
+                    address: query.address.into(),
+                    pagination: query.pagination,
+                }
+            }
+        }
+
+        impl Protobuf<RawQueryAddressRequest> for QueryAddressRequest {}
+
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct RawMsgRegisterContributor {
+            #[prost(string, tag = "1")]
+            pub address: String,
+        }
+
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+        pub struct MsgRegisterContributor {
+            pub address: AccAddress,
+        }
+
+        impl TryFrom<RawMsgRegisterContributor> for MsgRegisterContributor {
+            type Error = Error;
+
+            fn try_from(raw: RawMsgRegisterContributor) -> Result<Self, Self::Error> {
+                let address = AccAddress::from_bech32(&raw.address)
+                    .map_err(|e| Error::DecodeAddress(e.to_string()))?;
+
+                Ok(MsgRegisterContributor { address })
+            }
+        }
+
+        impl From<MsgRegisterContributor> for RawMsgRegisterContributor {
+            fn from(msg: MsgRegisterContributor) -> RawMsgRegisterContributor {
+                RawMsgRegisterContributor {
+                    address: msg.address.into(),
+                }
+            }
+        }
+
+        impl Protobuf<RawMsgRegisterContributor> for MsgRegisterContributor {}
+
+        //TODO: should to Any be implemented at the individual message type?
+        impl From<MsgRegisterContributor> for Any {
+            fn from(msg: MsgRegisterContributor) -> Self {
+                Any {
+                    type_url: "/tlcs.timelock.v1beta1.MsgRegisterContributor".to_string(),
+                    value: msg.encode_vec(),
+                }
+            }
+        }
+
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct RawMsgDeregisterContributor {
+            #[prost(string, tag = "1")]
+            pub address: String,
+        }
+
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+        pub struct MsgDeregisterContributor {
+            pub address: AccAddress,
+        }
+
+        impl TryFrom<RawMsgDeregisterContributor> for MsgDeregisterContributor {
+            type Error = Error;
+
+            fn try_from(raw: RawMsgDeregisterContributor) -> Result<Self, Self::Error> {
+                let address = AccAddress::from_bech32(&raw.address)
+                    .map_err(|e| Error::DecodeAddress(e.to_string()))?;
+
+                Ok(MsgDeregisterContributor { address })
+            }
+        }
+
+        impl From<MsgDeregisterContributor> for RawMsgDeregisterContributor {
+            fn from(msg: MsgDeregisterContributor) -> RawMsgDeregisterContributor {
+                RawMsgDeregisterContributor {
+                    address: msg.address.into(),
+                }
+            }
+        }
+
+        impl Protobuf<RawMsgDeregisterContributor> for MsgDeregisterContributor {}
+
+        //TODO: should to Any be implemented at the individual message type?
+        impl From<MsgDeregisterContributor> for Any {
+            fn from(msg: MsgDeregisterContributor) -> Self {
+                Any {
+                    type_url: "/tlcs.timelock.v1beta1.MsgDeregisterContributor".to_string(),
+                    value: msg.encode_vec(),
+                }
+            }
+        }
+
+        /////////////////////////////////////////////////////////////////////////////////////
+        // Cancel Key Request Section
+        /////////////////////////////////////////////////////////////////////////////////////
+
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct RawMsgCancelKeyRequest {
+            #[prost(string, tag = "1")]
+            pub address: String,
+            #[prost(uint64, tag = "2")]
+            pub round: u64,
+            #[prost(uint32, tag = "3")]
+            pub scheme: u32,
+            #[prost(uint32, tag = "4")]
+            pub id: u32,
+        }
+
+        /// Withdraws a keypair request whose public key wasn't made yet. Only the address which
+        /// requested the keypair can send it.
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+        pub struct MsgCancelKeyRequest {
+            pub address: AccAddress,
+            pub round: u64,
+            pub scheme: u32,
+            pub id: u32,
+        }
+
+        impl TryFrom<RawMsgCancelKeyRequest> for MsgCancelKeyRequest {
+            type Error = Error;
+
+            fn try_from(raw: RawMsgCancelKeyRequest) -> Result<Self, Self::Error> {
+                let address = AccAddress::from_bech32(&raw.address)
+                    .map_err(|e| Error::DecodeAddress(e.to_string()))?;
+
+                Ok(MsgCancelKeyRequest {
+                    address,
+                    round: raw.round,
+                    scheme: raw.scheme,
+                    id: raw.id,
+                })
+            }
+        }
+
+        impl From<MsgCancelKeyRequest> for RawMsgCancelKeyRequest {
+            fn from(msg: MsgCancelKeyRequest) -> RawMsgCancelKeyRequest {
+                RawMsgCancelKeyRequest {
+                    address: msg.address.into(),
+                    round: msg.round,
+                    scheme: msg.scheme,
+                    id: msg.id,
+                }
+            }
+        }
+
+        impl Protobuf<RawMsgCancelKeyRequest> for MsgCancelKeyRequest {}
+
+        //TODO: should to Any be implemented at the individual message type?
+        impl From<MsgCancelKeyRequest> for Any {
+            fn from(msg: MsgCancelKeyRequest) -> Self {
+                Any {
+                    type_url: "/tlcs.timelock.v1beta1.MsgCancelKeyRequest".to_string(),
+                    value: msg.encode_vec(),
+                }
+            }
+        }
+
+        /////////////////////////////////////////////////////////////////////////////////////
+        // Fee Escrow Section
+        /////////////////////////////////////////////////////////////////////////////////////
+
+        /// Request fee held by the timelock module account until the keypair's public key is
+        /// made, or the request fails
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct RawFeeEscrow {
+            #[prost(uint64, tag = "1")]
+            pub round: u64,
+            #[prost(uint32, tag = "2")]
+            pub scheme: u32,
+            #[prost(uint32, tag = "3")]
+            pub id: u32,
+            #[prost(string, tag = "4")]
+            pub payer: String,
+            #[prost(uint64, tag = "5")]
+            pub amount: u64,
+            #[prost(string, tag = "6")]
+            pub denom: String,
+        }
+
+        /////////////////////////////////////////////////////////////////////////////////////
+        // Update Params Section
+        /////////////////////////////////////////////////////////////////////////////////////
+
+        /// Only the fields which are set are changed, the changes are applied at the start
+        /// of block `height`.
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct RawMsgUpdateParams {
+            #[prost(string, tag = "1")]
+            pub authority: String,
+            #[prost(uint64, tag = "2")]
+            pub height: u64,
+            #[prost(uint32, optional, tag = "7")]
+            pub security_param: Option<u32>,
+            #[prost(uint32, optional, tag = "8")]
+            pub min_contribution_threshold: Option<u32>,
+            #[prost(uint32, optional, tag = "9")]
+            pub max_multi_requests: Option<u32>,
+            #[prost(uint32, optional, tag = "10")]
+            pub min_pubkey_lead_time: Option<u32>,
+            #[prost(string, optional, tag = "11")]
+            pub bond_denom: Option<String>,
+            #[prost(uint64, optional, tag = "12")]
+            pub contributor_bond: Option<u64>,
+            #[prost(uint32, optional, tag = "13")]
+            pub threshold_numerator: Option<u32>,
+            #[prost(uint32, optional, tag = "14")]
+            pub threshold_denominator: Option<u32>,
+            #[prost(uint64, optional, tag = "15")]
+            pub request_fee: Option<u64>,
+            #[prost(uint32, optional, tag = "16")]
+            pub min_participation_ratio: Option<u32>,
+            #[prost(uint64, optional, tag = "17")]
+            pub retention_blocks: Option<u64>,
+            #[prost(uint32, optional, tag = "18")]
+            pub max_prunes_per_block: Option<u32>,
+            #[prost(uint32, optional, tag = "19")]
+            pub max_key_derivations_per_block: Option<u32>,
+        }
+
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+        pub struct MsgUpdateParams {
+            pub authority: AccAddress,
+            pub height: u64,
+            pub security_param: Option<u32>,
+            pub min_contribution_threshold: Option<u32>,
+            pub max_multi_requests: Option<u32>,
+            pub min_pubkey_lead_time: Option<u32>,
+            pub bond_denom: Option<String>,
+            pub contributor_bond: Option<u64>,
+            pub threshold_numerator: Option<u32>,
+            pub threshold_denominator: Option<u32>,
+            pub request_fee: Option<u64>,
+            pub min_participation_ratio: Option<u32>,
+            pub retention_blocks: Option<u64>,
+            pub max_prunes_per_block: Option<u32>,
+            pub max_key_derivations_per_block: Option<u32>,
+        }
+
+        impl TryFrom<RawMsgUpdateParams> for MsgUpdateParams {
+            type Error = Error;
+
+            fn try_from(raw: RawMsgUpdateParams) -> Result<Self, Self::Error> {
+                let authority = AccAddress::from_bech32(&raw.authority)
+                    .map_err(|e| Error::DecodeAddress(e.to_string()))?;
+
+                Ok(MsgUpdateParams {
+                    authority,
+                    height: raw.height,
+                    security_param: raw.security_param,
+                    min_contribution_threshold: raw.min_contribution_threshold,
+                    max_multi_requests: raw.max_multi_requests,
+                    min_pubkey_lead_time: raw.min_pubkey_lead_time,
+                    bond_denom: raw.bond_denom,
+                    contributor_bond: raw.contributor_bond,
+                    threshold_numerator: raw.threshold_numerator,
+                    threshold_denominator: raw.threshold_denominator,
+                    request_fee: raw.request_fee,
+                    min_participation_ratio: raw.min_participation_ratio,
+                    retention_blocks: raw.retention_blocks,
+                    max_prunes_per_block: raw.max_prunes_per_block,
+                    max_key_derivations_per_block: raw.max_key_derivations_per_block,
+                })
+            }
+        }
+
+        impl From<MsgUpdateParams> for RawMsgUpdateParams {
+            fn from(msg: MsgUpdateParams) -> RawMsgUpdateParams {
+                RawMsgUpdateParams {
+                    authority: msg.authority.into(),
+                    height: msg.height,
+                    security_param: msg.security_param,
+                    min_contribution_threshold: msg.min_contribution_threshold,
+                    max_multi_requests: msg.max_multi_requests,
+                    min_pubkey_lead_time: msg.min_pubkey_lead_time,
+                    bond_denom: msg.bond_denom,
+                    contributor_bond: msg.contributor_bond,
+                    threshold_numerator: msg.threshold_numerator,
+                    threshold_denominator: msg.threshold_denominator,
+                    request_fee: msg.request_fee,
+                    min_participation_ratio: msg.min_participation_ratio,
+                    retention_blocks: msg.retention_blocks,
+                    max_prunes_per_block: msg.max_prunes_per_block,
+                    max_key_derivations_per_block: msg.max_key_derivations_per_block,
+                }
+            }
+        }
+
+        impl Protobuf<RawMsgUpdateParams> for MsgUpdateParams {}
+
+        //TODO: should to Any be implemented at the individual message type?
+        impl From<MsgUpdateParams> for Any {
+            fn from(msg: MsgUpdateParams) -> Self {
+                Any {
+                    type_url: "/tlcs.timelock.v1beta1.MsgUpdateParams".to_string(),
+                    value: msg.encode_vec(),
+                }
+            }
+        }
+
+        /////////////////////////////////////////////////////////////////////////////////////
+        // Beacon Registry Section
+        /////////////////////////////////////////////////////////////////////////////////////
+
+        /// A drand network which keypairs can be requested for. Retired beacons can't be
+        /// selected by new requests, but their LOE data is still accepted for the keypairs
+        /// which use them.
+        #[derive(Serialize, Deserialize, Clone, PartialEq, Message)]
+        pub struct RawBeacon {
+            #[prost(uint32, tag = "1")]
+            pub id: u32,
+            #[prost(string, tag = "2")]
+            pub chain_hash: String,
+            #[prost(string, tag = "3")]
+            pub public_key: String,
+            #[prost(uint32, tag = "4")]
+            pub period: u32,
+            #[prost(int64, tag = "5")]
+            pub genesis_time: i64,
+            #[prost(string, tag = "6")]
+            pub scheme: String,
+            #[prost(bool, tag = "7")]
+            pub retired: bool,
+            /// Url of the beacon's chain on a drand http api, the rounds are fetched from it
+            #[prost(string, tag = "8")]
+            pub url: String,
+        }
+
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct QueryBeaconsResponse {
+            #[prost(message, repeated, tag = "1")]
+            pub beacons: Vec<RawBeacon>,
+            #[prost(message, optional, tag = "2")]
+            pub pagination: Option<PageResponse>,
+        }
+
+        impl Protobuf<QueryBeaconsResponse> for QueryBeaconsResponse {}
+
+        /// Registers a beacon, or retires or reactivates a registered one. The chain info of
+        /// a registered beacon can't change since its keypairs depend on it, only its url can.
+        #[derive(Serialize, Deserialize, Clone, Message)]
+        pub struct RawMsgUpdateBeacon {
+            #[prost(string, tag = "1")]
+            pub authority: String,
+            #[prost(message, optional, tag = "2")]
+            pub beacon: Option<RawBeacon>,
+        }
+
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+        pub struct MsgUpdateBeacon {
+            pub authority: AccAddress,
+            pub beacon: RawBeacon,
+        }
+
+        impl TryFrom<RawMsgUpdateBeacon> for MsgUpdateBeacon {
+            type Error = Error;
+
+            fn try_from(raw: RawMsgUpdateBeacon) -> Result<Self, Self::Error> {
+                let authority = AccAddress::from_bech32(&raw.authority)
+                    .map_err(|e| Error::DecodeAddress(e.to_string()))?;
+                let beacon = raw
+                    .beacon
+                    .ok_or_else(|| Error::DecodeGeneral("missing beacon".into()))?;
+
+                Ok(MsgUpdateBeacon { authority, beacon })
+            }
+        }
+
+        impl From<MsgUpdateBeacon> for RawMsgUpdateBeacon {
+            fn from(msg: MsgUpdateBeacon) -> RawMsgUpdateBeacon {
+                RawMsgUpdateBeacon {
+                    authority: msg.authority.into(),
+                    beacon: Some(msg.beacon),
+                }
+            }
+        }
+
+        impl Protobuf<RawMsgUpdateBeacon> for MsgUpdateBeacon {}
+
+        //TODO: should to Any be implemented at the individual message type?
+        impl From<MsgUpdateBeacon> for Any {
+            fn from(msg: MsgUpdateBeacon) -> Self {
+                Any {
+                    type_url: "/tlcs.timelock.v1beta1.MsgUpdateBeacon".to_string(),
+                    value: msg.encode_vec(),
+                }
+            }
+        }
     }
 }